@@ -0,0 +1,299 @@
+use std::path::Path;
+
+use crate::Symbol;
+
+/// Comprimento máximo do comando mostrado para `ENTRYPOINT`/`CMD`
+const MAX_COMMAND_LEN: usize = 60;
+
+/// Linha lógica (com continuações `\` unidas) e a sua localização
+struct LogicalLine {
+    text: String,
+    start_line: usize,
+    end_line: usize,
+    start_byte: usize,
+    end_byte: usize,
+}
+
+/// Verifica se o ficheiro é um Makefile (`Makefile`, `GNUmakefile`, `*.mk`)
+pub(crate) fn is_makefile_path(file_path: &Path) -> bool {
+    let name = file_name_lowercase(file_path);
+    name == "makefile"
+        || name == "gnumakefile"
+        || name.ends_with(".mk")
+        || name.ends_with(".makefile")
+}
+
+/// Verifica se o ficheiro é um Dockerfile (`Dockerfile`, `Dockerfile.dev`, `app.dockerfile`)
+pub(crate) fn is_dockerfile_path(file_path: &Path) -> bool {
+    let name = file_name_lowercase(file_path);
+    name == "dockerfile" || name.starts_with("dockerfile.") || name.ends_with(".dockerfile")
+}
+
+/// Verifica se o ficheiro descreve a construção ou execução do projeto
+pub(crate) fn is_build_file(file_path: &Path) -> bool {
+    is_makefile_path(file_path) || is_dockerfile_path(file_path)
+}
+
+fn file_name_lowercase(file_path: &Path) -> String {
+    file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Extrai os alvos de um Makefile ou as fases de um Dockerfile, pela ordem do documento
+pub(crate) fn extract_build_symbols(file_path: &Path, content: &str) -> Vec<Symbol> {
+    if is_makefile_path(file_path) {
+        extract_makefile_symbols(content)
+    } else if is_dockerfile_path(file_path) {
+        extract_dockerfile_symbols(content)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Alvos do Makefile (`target: deps`) com as dependências como detalhe.
+/// Alvos especiais (`.PHONY`), regras de padrão (`%.o`) e variáveis são ignorados.
+fn extract_makefile_symbols(content: &str) -> Vec<Symbol> {
+    let lines = logical_lines(content, false);
+    let mut symbols = Vec::new();
+    let mut in_define = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        // As receitas começam com tab e pertencem ao alvo anterior
+        if line.text.starts_with('\t') {
+            continue;
+        }
+        let text = strip_comment(&line.text).trim();
+
+        if text.starts_with("define ") || text == "define" {
+            in_define = true;
+            continue;
+        }
+        if in_define {
+            in_define = text != "endef";
+            continue;
+        }
+
+        let Some((targets, prerequisites)) = split_rule(text) else {
+            continue;
+        };
+
+        // A regra termina na última linha de receita
+        let recipe_end = lines[index + 1..]
+            .iter()
+            .take_while(|next| next.text.starts_with('\t') || next.text.trim().is_empty())
+            .filter(|next| next.text.starts_with('\t'))
+            .last()
+            .unwrap_or(line);
+
+        let dependencies: Vec<&str> = prerequisites
+            .split_whitespace()
+            .filter(|dependency| *dependency != "|")
+            .collect();
+        let detail = (!dependencies.is_empty()).then(|| dependencies.join(" "));
+
+        for target in targets.split_whitespace() {
+            if target.starts_with('.') || target.contains('%') {
+                continue;
+            }
+            symbols.push(Symbol {
+                kind: "target".to_string(),
+                name: target.to_string(),
+                start_line: line.start_line,
+                end_line: recipe_end.end_line,
+                start_byte: line.start_byte,
+                end_byte: recipe_end.end_byte,
+                detail: detail.clone(),
+                container: None,
+            });
+        }
+    }
+
+    symbols
+}
+
+/// Divide `alvos: dependências` ignorando atribuições (`X := y`, `X = y`, `X ?= y`)
+fn split_rule(text: &str) -> Option<(&str, &str)> {
+    let colon = text.find(':')?;
+    let targets = &text[..colon];
+    let rest = text[colon + 1..].trim_start_matches(':');
+
+    if targets.contains('=') || rest.starts_with('=') || targets.trim().is_empty() {
+        return None;
+    }
+    // Variáveis específicas de um alvo (`debug: CFLAGS += -g`)
+    let prerequisites = rest.split(';').next().unwrap_or_default();
+    if prerequisites.contains('=') && !prerequisites.contains("$(") {
+        return None;
+    }
+
+    Some((targets.trim(), prerequisites.trim()))
+}
+
+/// Fases (`FROM ... AS nome`), portas expostas e comandos de arranque de um Dockerfile.
+/// Portas e comandos ficam aninhados na fase onde são declarados.
+fn extract_dockerfile_symbols(content: &str) -> Vec<Symbol> {
+    let lines: Vec<LogicalLine> = logical_lines(content, true)
+        .into_iter()
+        .filter(|line| {
+            let text = line.text.trim_start();
+            !text.is_empty() && !text.starts_with('#')
+        })
+        .collect();
+    let mut symbols: Vec<Symbol> = Vec::new();
+    let mut stage_index: Option<usize> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let text = line.text.trim();
+        let (instruction, arguments) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let arguments = arguments.trim();
+        let stage = stage_index.map(|i| symbols[i].name.clone());
+
+        match instruction.to_uppercase().as_str() {
+            "FROM" => {
+                // Ignorar opções como `--platform=...`
+                let words: Vec<&str> = arguments
+                    .split_whitespace()
+                    .filter(|word| !word.starts_with("--"))
+                    .collect();
+                let Some(image) = words.first() else {
+                    continue;
+                };
+                let alias = words
+                    .iter()
+                    .position(|word| word.eq_ignore_ascii_case("as"))
+                    .and_then(|i| words.get(i + 1));
+
+                // A fase vai até à instrução anterior ao próximo `FROM`
+                let last = lines[index + 1..]
+                    .iter()
+                    .take_while(|next| !is_instruction(&next.text, "FROM"))
+                    .last()
+                    .unwrap_or(line);
+
+                stage_index = Some(symbols.len());
+                symbols.push(Symbol {
+                    kind: "stage".to_string(),
+                    name: alias.unwrap_or(image).to_string(),
+                    start_line: line.start_line,
+                    end_line: last.end_line,
+                    start_byte: line.start_byte,
+                    end_byte: last.end_byte,
+                    detail: alias.map(|_| image.to_string()),
+                    container: None,
+                });
+            }
+            "EXPOSE" => {
+                for port in arguments.split_whitespace() {
+                    symbols.push(line_symbol(line, "port", port, stage.clone()));
+                }
+            }
+            "ENTRYPOINT" | "CMD" => {
+                let kind = instruction.to_lowercase();
+                symbols.push(line_symbol(
+                    line,
+                    &kind,
+                    &command_text(arguments),
+                    stage.clone(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    symbols
+}
+
+fn is_instruction(text: &str, instruction: &str) -> bool {
+    text.split_whitespace()
+        .next()
+        .is_some_and(|word| word.eq_ignore_ascii_case(instruction))
+}
+
+fn line_symbol(line: &LogicalLine, kind: &str, name: &str, container: Option<String>) -> Symbol {
+    Symbol {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        start_line: line.start_line,
+        end_line: line.end_line,
+        start_byte: line.start_byte,
+        end_byte: line.end_byte,
+        detail: None,
+        container,
+    }
+}
+
+/// Comando de `CMD`/`ENTRYPOINT` em forma legível: a forma exec (`["a", "b"]`)
+/// é convertida em `a b`, e comandos longos são truncados
+fn command_text(arguments: &str) -> String {
+    let command = match arguments
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        Some(array) => array
+            .split(',')
+            .map(|part| part.trim().trim_matches('"'))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => arguments.split_whitespace().collect::<Vec<_>>().join(" "),
+    };
+
+    if command.chars().count() > MAX_COMMAND_LEN {
+        let truncated: String = command.chars().take(MAX_COMMAND_LEN).collect();
+        format!("{}…", truncated)
+    } else {
+        command
+    }
+}
+
+/// Remove um comentário `#` do fim de uma linha de Makefile
+fn strip_comment(text: &str) -> &str {
+    text.find('#').map_or(text, |pos| &text[..pos])
+}
+
+/// Junta as linhas terminadas em `\` com a seguinte. Num Dockerfile os comentários
+/// entre linhas continuadas são descartados.
+fn logical_lines(content: &str, skip_comments_in_continuation: bool) -> Vec<LogicalLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<LogicalLine> = None;
+    let mut byte = 0;
+
+    for (index, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_start = byte;
+        byte += raw_line.len();
+        let text = raw_line.trim_end_matches(['\n', '\r']);
+
+        let logical = match current.take() {
+            Some(mut logical) => {
+                logical.end_line = index + 1;
+                logical.end_byte = line_start + text.len();
+                if skip_comments_in_continuation && text.trim_start().starts_with('#') {
+                    current = Some(logical);
+                    continue;
+                }
+                logical.text.push(' ');
+                logical.text.push_str(text.trim_start());
+                logical
+            }
+            None => LogicalLine {
+                text: text.to_string(),
+                start_line: index + 1,
+                end_line: index + 1,
+                start_byte: line_start,
+                end_byte: line_start + text.len(),
+            },
+        };
+
+        if logical.text.ends_with('\\') {
+            let mut logical = logical;
+            logical.text.pop();
+            current = Some(logical);
+        } else {
+            lines.push(logical);
+        }
+    }
+
+    lines.extend(current);
+    lines
+}
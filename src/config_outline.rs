@@ -0,0 +1,434 @@
+use std::path::Path;
+
+use crate::Symbol;
+
+/// Formatos de configuração suportados
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+/// Esquema conhecido que dá nomes próprios às chaves de segundo nível
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KnownSchema {
+    /// `.github/workflows/*.yml`: `jobs`
+    GithubWorkflow,
+    /// `docker-compose.yml`/`compose.yaml`: `services`
+    Compose,
+    /// `Cargo.toml`: `[dependencies]`, `[dev-dependencies]`, ...
+    CargoManifest,
+    /// `package.json`: `scripts` e `dependencies`
+    PackageJson,
+    Generic,
+}
+
+fn config_format(file_path: &Path) -> Option<ConfigFormat> {
+    match file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .as_deref()
+    {
+        Some("toml") => Some(ConfigFormat::Toml),
+        Some("yaml") | Some("yml") => Some(ConfigFormat::Yaml),
+        Some("json") => Some(ConfigFormat::Json),
+        _ => None,
+    }
+}
+
+/// Verifica se o ficheiro é TOML, YAML ou JSON
+pub(crate) fn is_config_path(file_path: &Path) -> bool {
+    config_format(file_path).is_some()
+}
+
+fn known_schema(file_path: &Path) -> KnownSchema {
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let path = file_path.to_string_lossy().replace('\\', "/");
+
+    if path.contains(".github/workflows/") {
+        KnownSchema::GithubWorkflow
+    } else if file_name.starts_with("docker-compose") || file_name.starts_with("compose.") {
+        KnownSchema::Compose
+    } else if file_name == "cargo.toml" {
+        KnownSchema::CargoManifest
+    } else if file_name == "package.json" {
+        KnownSchema::PackageJson
+    } else {
+        KnownSchema::Generic
+    }
+}
+
+/// Chave encontrada no ficheiro: nível 0 (topo) ou 1 (aninhada), com o valor em linha
+struct ConfigKey {
+    level: usize,
+    name: String,
+    value: Option<String>,
+    line: usize,
+    byte: usize,
+}
+
+/// Extrai um outline superficial (chaves de topo e um nível aninhado) de TOML, YAML ou JSON.
+/// As chaves aninhadas ficam com `container` igual à chave de topo.
+pub(crate) fn extract_config_symbols(file_path: &Path, content: &str) -> Vec<Symbol> {
+    let Some(format) = config_format(file_path) else {
+        return vec![];
+    };
+    let schema = known_schema(file_path);
+
+    let keys = match format {
+        ConfigFormat::Toml => scan_toml_keys(content),
+        ConfigFormat::Yaml => scan_yaml_keys(content),
+        ConfigFormat::Json => scan_json_keys(content),
+    };
+
+    let total_lines = content.lines().count().max(1);
+    let mut symbols = Vec::new();
+    let mut current_parent: Option<String> = None;
+
+    for (i, key) in keys.iter().enumerate() {
+        // A chave termina onde começa a próxima do mesmo nível ou superior
+        let next = keys[i + 1..].iter().find(|other| other.level <= key.level);
+        let (end_line, end_byte) = match next {
+            Some(next) => (next.line.saturating_sub(1).max(key.line), next.byte),
+            None => (total_lines, content.len()),
+        };
+
+        let (kind, detail, container) = if key.level == 0 {
+            current_parent = Some(key.name.clone());
+            let kind = if format == ConfigFormat::Toml && key.value.is_none() {
+                "table"
+            } else {
+                "key"
+            };
+            (kind, None, None)
+        } else {
+            let parent = current_parent.clone();
+            let (kind, detail) =
+                nested_key_kind(schema, parent.as_deref().unwrap_or_default(), key);
+            (kind, detail, parent)
+        };
+
+        symbols.push(Symbol {
+            kind: kind.to_string(),
+            name: key.name.clone(),
+            start_line: key.line,
+            end_line,
+            start_byte: key.byte,
+            end_byte,
+            detail,
+            container,
+        });
+    }
+
+    symbols
+}
+
+/// Tipo de uma chave aninhada segundo o esquema reconhecido
+fn nested_key_kind(
+    schema: KnownSchema,
+    parent: &str,
+    key: &ConfigKey,
+) -> (&'static str, Option<String>) {
+    match (schema, parent) {
+        (KnownSchema::GithubWorkflow, "jobs") => ("job", None),
+        (KnownSchema::Compose, "services") => ("service", None),
+        (
+            KnownSchema::CargoManifest,
+            "dependencies" | "dev-dependencies" | "build-dependencies" | "workspace.dependencies",
+        ) => (
+            "dependency",
+            key.value.as_deref().and_then(dependency_version),
+        ),
+        (KnownSchema::CargoManifest, table)
+            if table.starts_with("target.") && table.ends_with("dependencies") =>
+        {
+            (
+                "dependency",
+                key.value.as_deref().and_then(dependency_version),
+            )
+        }
+        (
+            KnownSchema::PackageJson,
+            "dependencies" | "devDependencies" | "peerDependencies" | "optionalDependencies",
+        ) => (
+            "dependency",
+            key.value.as_deref().and_then(dependency_version),
+        ),
+        (KnownSchema::PackageJson, "scripts") => ("script", None),
+        _ => ("key", None),
+    }
+}
+
+/// Versão de uma dependência: `"1.0"` ou `{ version = "1.0", ... }`
+fn dependency_version(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(stripped) = value.strip_prefix('"').or_else(|| value.strip_prefix('\'')) {
+        return stripped
+            .split(['"', '\''])
+            .next()
+            .map(|version| version.to_string());
+    }
+    let start = value.find("version")?;
+    let after = &value[start + "version".len()..];
+    let after = after.trim_start().strip_prefix(['=', ':'])?.trim_start();
+    let after = after.strip_prefix(['"', '\''])?;
+    after
+        .split(['"', '\''])
+        .next()
+        .map(|version| version.to_string())
+}
+
+/// Linhas de um conteúdo com o respetivo offset em bytes, sem o fim de linha
+fn lines_with_offsets(content: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    let mut byte = 0;
+    content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(move |(index, raw_line)| {
+            let start = byte;
+            byte += raw_line.len();
+            (index + 1, start, raw_line.trim_end_matches(['\n', '\r']))
+        })
+}
+
+/// TOML: cabeçalhos `[tabela]`/`[[tabela]]` no topo e chaves `a = b` dentro deles
+fn scan_toml_keys(content: &str) -> Vec<ConfigKey> {
+    let mut keys = Vec::new();
+    let mut in_table = false;
+    let mut multiline_string: Option<&str> = None;
+    let mut bracket_depth: i32 = 0;
+
+    for (line, byte, text) in lines_with_offsets(content) {
+        let trimmed = text.trim();
+
+        // Continuação de strings ou arrays multi-linha
+        if let Some(delimiter) = multiline_string {
+            if trimmed.contains(delimiter) {
+                multiline_string = None;
+            }
+            continue;
+        }
+        if bracket_depth > 0 {
+            bracket_depth += bracket_balance(trimmed);
+            continue;
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            let name = trimmed
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim();
+            keys.push(ConfigKey {
+                level: 0,
+                name: name.to_string(),
+                value: None,
+                line,
+                byte,
+            });
+            in_table = true;
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once('=') {
+            let key = key.trim().trim_matches(['"', '\'']);
+            let value = value.trim();
+            for delimiter in ["\"\"\"", "'''"] {
+                if value.starts_with(delimiter) && value.matches(delimiter).count() == 1 {
+                    multiline_string = Some(delimiter);
+                }
+            }
+            bracket_depth = bracket_balance(value).max(0);
+
+            keys.push(ConfigKey {
+                level: usize::from(in_table),
+                name: key.to_string(),
+                value: Some(value.to_string()),
+                line,
+                byte,
+            });
+        }
+    }
+
+    keys
+}
+
+/// Diferença entre `[`/`{` abertos e fechados, ignorando o conteúdo de strings
+fn bracket_balance(text: &str) -> i32 {
+    let mut balance = 0;
+    let mut quote: Option<char> = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => break,
+            (None, '[' | '{') => balance += 1,
+            (None, ']' | '}') => balance -= 1,
+            _ => {}
+        }
+    }
+    balance
+}
+
+/// YAML: chaves sem indentação e as chaves do primeiro nível de indentação abaixo delas
+fn scan_yaml_keys(content: &str) -> Vec<ConfigKey> {
+    let mut keys = Vec::new();
+    let mut child_indent: Option<usize> = None;
+    let mut block_scalar_indent: Option<usize> = None;
+
+    for (line, byte, text) in lines_with_offsets(content) {
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" || trimmed == "..." {
+            continue;
+        }
+        let indent = text.len() - text.trim_start().len();
+
+        // Conteúdo de `key: |` ou `key: >` pertence ao valor, não tem chaves
+        if let Some(scalar_indent) = block_scalar_indent {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+
+        if trimmed.starts_with('-') {
+            continue;
+        }
+        let Some((key, value)) = split_yaml_key(trimmed) else {
+            continue;
+        };
+        if value.starts_with('|') || value.starts_with('>') {
+            block_scalar_indent = Some(indent);
+        }
+
+        if indent == 0 {
+            child_indent = None;
+            keys.push(ConfigKey {
+                level: 0,
+                name: key,
+                value: (!value.is_empty()).then(|| value.to_string()),
+                line,
+                byte,
+            });
+        } else if keys.last().is_some() && *child_indent.get_or_insert(indent) == indent {
+            keys.push(ConfigKey {
+                level: 1,
+                name: key,
+                value: (!value.is_empty()).then(|| value.to_string()),
+                line,
+                byte,
+            });
+        }
+    }
+
+    keys
+}
+
+/// Separa `chave: valor` numa linha YAML, removendo aspas da chave
+fn split_yaml_key(line: &str) -> Option<(String, &str)> {
+    let (key, value) = if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'')
+    {
+        let end = line[1..].find(quote)? + 1;
+        let rest = line[end + 1..].trim_start().strip_prefix(':')?;
+        (line[1..end].to_string(), rest)
+    } else {
+        let colon = line
+            .find(": ")
+            .or_else(|| line.strip_suffix(':').map(|k| k.len()))?;
+        (line[..colon].trim().to_string(), &line[colon + 1..])
+    };
+    if key.is_empty() || (key.contains(' ') && !line.starts_with(['"', '\''])) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+/// JSON (com comentários, como tsconfig.json): chaves dos dois primeiros níveis de objetos
+fn scan_json_keys(content: &str) -> Vec<ConfigKey> {
+    let mut keys = Vec::new();
+    let bytes = content.as_bytes();
+    let mut depth: usize = 0;
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\n' => {
+                line += 1;
+                line_start = i + 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                        line_start = i + 1;
+                    }
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end] != b'"' {
+                    if bytes[end] == b'\\' {
+                        end += 1;
+                    }
+                    end += 1;
+                }
+                let string = &content[start..end.min(content.len())];
+                i = end + 1;
+
+                // Uma string seguida de `:` é uma chave
+                let rest = content[i.min(content.len())..].trim_start();
+                if let Some(after_colon) = rest.strip_prefix(':')
+                    && (1..=2).contains(&depth)
+                {
+                    let value = after_colon.trim_start();
+                    let value = (!value.starts_with(['{', '['])).then(|| {
+                        value
+                            .split([',', '\n', '}'])
+                            .next()
+                            .unwrap_or_default()
+                            .trim()
+                            .to_string()
+                    });
+                    keys.push(ConfigKey {
+                        level: depth - 1,
+                        name: string.to_string(),
+                        value,
+                        line,
+                        byte: line_start,
+                    });
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    keys
+}
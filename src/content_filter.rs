@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use crate::markdown;
+
+/// Bytes iniciais onde se procuram NULs, como faz o git para detetar binários
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Bytes iniciais onde se procuram os marcadores de código gerado
+const GENERATED_HEADER_BYTES: usize = 2048;
+
+/// Início das linhas de comentário onde os marcadores de código gerado são procurados
+const COMMENT_PREFIXES: [&str; 7] = ["//", "#", "/*", "*", "<!--", "--", ";"];
+
+/// Comprimento médio de linha a partir do qual o ficheiro é considerado minificado
+const MINIFIED_AVERAGE_LINE: usize = 250;
+
+/// Ficheiros mais pequenos do que isto nunca são considerados minificados
+const MINIFIED_MIN_BYTES: usize = 2048;
+
+/// Motivo para não analisar o conteúdo: binário, minificado ou gerado.
+/// `None` se o ficheiro deve ser analisado normalmente.
+pub(crate) fn skip_reason(file_path: &Path, bytes: &[u8]) -> Option<String> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return Some("binary content".to_string());
+    }
+    let Ok(content) = std::str::from_utf8(bytes) else {
+        return Some("binary content: invalid UTF-8".to_string());
+    };
+
+    if let Some(marker) = generated_marker(content) {
+        return Some(format!("generated: {}", marker));
+    }
+
+    // Parágrafos de Markdown ocupam uma linha inteira sem serem minificados
+    if bytes.len() >= MINIFIED_MIN_BYTES && !markdown::is_markdown_path(file_path) {
+        let lines = content.lines().count().max(1);
+        let average = bytes.len() / lines;
+        if average >= MINIFIED_AVERAGE_LINE {
+            return Some(format!("minified: average line length {}", average));
+        }
+    }
+
+    None
+}
+
+/// Marcador de código gerado num comentário do cabeçalho: `@generated` ou a convenção
+/// do Go, `Code generated ... DO NOT EDIT.` numa única linha. Ambos distinguem
+/// maiúsculas, para não apanhar texto como "please do not edit this header".
+fn generated_marker(content: &str) -> Option<&'static str> {
+    let mut end = content.len().min(GENERATED_HEADER_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    for line in content[..end].lines() {
+        let line = line.trim();
+        let Some(text) = COMMENT_PREFIXES
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))
+        else {
+            continue;
+        };
+        if contains_unquoted(line, "@generated") {
+            return Some("@generated marker");
+        }
+        let text = text.trim_end_matches("*/").trim_end_matches("-->").trim();
+        if text.starts_with("Code generated ") && text.ends_with("DO NOT EDIT.") {
+            return Some("Code generated ... DO NOT EDIT. header");
+        }
+    }
+    None
+}
+
+/// O marcador aparece fora de aspas ou backticks, e não apenas citado
+fn contains_unquoted(line: &str, marker: &str) -> bool {
+    line.match_indices(marker)
+        .any(|(index, _)| !line[..index].ends_with(['`', '"', '\'']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(name: &str, content: &[u8]) -> Option<String> {
+        skip_reason(Path::new(name), content)
+    }
+
+    #[test]
+    fn nul_bytes_are_binary() {
+        assert_eq!(
+            reason("image.rs", b"\x89PNG\r\n\x00\x00"),
+            Some("binary content".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_is_binary() {
+        assert_eq!(
+            reason("latin1.rs", b"fn caf\xe9() {}"),
+            Some("binary content: invalid UTF-8".to_string())
+        );
+    }
+
+    #[test]
+    fn long_lines_are_minified() {
+        let bundle = format!("var a=1;{}\n", "b(),".repeat(1000));
+        let minified = reason("bundle.js", bundle.as_bytes()).unwrap_or_default();
+        assert!(minified.starts_with("minified:"), "{}", minified);
+
+        // Parágrafos de Markdown e ficheiros pequenos não contam
+        let paragraph = "word ".repeat(1000);
+        assert_eq!(reason("README.md", paragraph.as_bytes()), None);
+        assert_eq!(reason("small.js", "x(),".repeat(100).as_bytes()), None);
+    }
+
+    #[test]
+    fn generated_headers_are_skipped() {
+        assert_eq!(
+            reason(
+                "api.pb.go",
+                b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"
+            ),
+            Some("generated: Code generated ... DO NOT EDIT. header".to_string())
+        );
+        assert_eq!(
+            reason(
+                "schema.rs",
+                b"/* Code generated by tool. DO NOT EDIT. */\nfn a() {}\n"
+            ),
+            Some("generated: Code generated ... DO NOT EDIT. header".to_string())
+        );
+        assert_eq!(
+            reason("Foo.java", b"/**\n * @generated\n */\nclass Foo {}\n"),
+            Some("generated: @generated marker".to_string())
+        );
+    }
+
+    #[test]
+    fn prose_about_editing_is_not_generated() {
+        assert_eq!(
+            reason(
+                "lib.rs",
+                b"// Copyright ACME. Please do not edit this license header.\nfn main() {}\n"
+            ),
+            None
+        );
+        assert_eq!(
+            reason("a.rs", b"// DO NOT EDIT without review\nfn a() {}\n"),
+            None
+        );
+        assert_eq!(
+            reason("b.go", b"// code generated by hand, do not edit.\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn markers_outside_comments_or_quoted_are_ignored() {
+        assert_eq!(
+            reason("a.rs", b"const MARKER: &str = \"@generated\";\n"),
+            None
+        );
+        assert_eq!(
+            reason("b.rs", b"// Detects the `@generated` marker\n"),
+            None
+        );
+        let late = format!("{}// @generated\n", "fn a() {}\n".repeat(300));
+        assert_eq!(reason("c.rs", late.as_bytes()), None);
+    }
+}
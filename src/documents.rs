@@ -0,0 +1,203 @@
+use jsonrpc_stdio_server::jsonrpc_core::{Params, Value};
+use std::path::{Path, PathBuf};
+
+use crate::resolve_workspace_dir;
+use crate::workspace_watcher::{Workspace, Workspaces, lock};
+
+/// `textDocument/didOpen`: passa a usar o texto do editor para o ficheiro
+pub(crate) fn did_open(workspaces: &Workspaces, params: Params) {
+    let params = params_value(params);
+    let (Some(path), Some(text)) = (
+        document_path(&params),
+        params["textDocument"]["text"].as_str(),
+    ) else {
+        return;
+    };
+
+    with_workspace(workspaces, &path, |workspace| {
+        workspace
+            .index
+            .set_document(&path, text.to_string(), &workspace.config);
+    });
+}
+
+/// `textDocument/didChange`: aplica as alterações (texto completo ou intervalos)
+/// e volta a extrair os símbolos com parse incremental
+pub(crate) fn did_change(workspaces: &Workspaces, params: Params) {
+    let params = params_value(params);
+    let Some(path) = document_path(&params) else {
+        return;
+    };
+    let Some(changes) = params["contentChanges"].as_array() else {
+        return;
+    };
+
+    with_workspace(workspaces, &path, |workspace| {
+        let Some(mut text) = workspace.index.document_text(&path) else {
+            return;
+        };
+        for change in changes {
+            apply_content_change(&mut text, change);
+        }
+        workspace.index.set_document(&path, text, &workspace.config);
+    });
+}
+
+/// `textDocument/didClose`: volta a usar o conteúdo gravado no disco
+pub(crate) fn did_close(workspaces: &Workspaces, params: Params) {
+    let params = params_value(params);
+    let Some(path) = document_path(&params) else {
+        return;
+    };
+
+    with_workspace(workspaces, &path, |workspace| {
+        workspace.index.close_document(&path, &workspace.config);
+    });
+}
+
+fn params_value(params: Params) -> Value {
+    match params {
+        Params::Map(map) => Value::Object(map),
+        Params::Array(array) => Value::Array(array),
+        Params::None => Value::Null,
+    }
+}
+
+/// Executa `apply` no workspace que contém o ficheiro: um dos já abertos ou, se
+/// nenhum o contiver, o workspace por omissão
+fn with_workspace(workspaces: &Workspaces, path: &Path, apply: impl FnOnce(&mut Workspace)) {
+    let workspace = workspaces.find_for(path).or_else(|| {
+        resolve_workspace_dir(None)
+            .ok()
+            .filter(|root| path.starts_with(root))
+            .map(|root| workspaces.get(&root))
+    });
+
+    match workspace {
+        Some(workspace) => apply(&mut lock(&workspace)),
+        None => eprintln!("⚠️  Document outside the workspace: {}", path.display()),
+    }
+}
+
+/// Caminho do documento a partir do URI `file://` (ou de um caminho simples)
+fn document_path(params: &Value) -> Option<PathBuf> {
+    let uri = params["textDocument"]["uri"].as_str()?;
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    Some(PathBuf::from(percent_decode(path)))
+}
+
+/// Descodifica `%XX` nos URIs (espaços e caracteres não ASCII nos caminhos)
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = text
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Aplica uma alteração do protocolo LSP: com `range` substitui o intervalo, sem ele
+/// substitui o documento inteiro
+fn apply_content_change(text: &mut String, change: &Value) {
+    let Some(new_text) = change["text"].as_str() else {
+        return;
+    };
+    let range = &change["range"];
+    if range.is_null() {
+        *text = new_text.to_string();
+        return;
+    }
+
+    let start = byte_offset(text, &range["start"]);
+    let end = byte_offset(text, &range["end"]).max(start);
+    text.replace_range(start..end, new_text);
+}
+
+/// Offset em bytes de uma posição LSP (linha base 0, coluna em unidades UTF-16)
+fn byte_offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+
+    let line_start: usize = text.split_inclusive('\n').take(line).map(str::len).sum();
+    // Em CRLF o `\r` pertence ao fim da linha: colunas além dele ficam antes do `\r`
+    let line_text = text[line_start..].split('\n').next().unwrap_or_default();
+    let line_text = line_text.strip_suffix('\r').unwrap_or(line_text);
+
+    let mut units = 0;
+    for (index, ch) in line_text.char_indices() {
+        if units >= character {
+            return line_start + index;
+        }
+        units += ch.len_utf16();
+    }
+    line_start + line_text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn position(line: u64, character: u64) -> Value {
+        json!({ "line": line, "character": character })
+    }
+
+    fn edited(text: &str, start: (u64, u64), end: (u64, u64), new_text: &str) -> String {
+        let mut text = text.to_string();
+        let change = json!({
+            "range": { "start": position(start.0, start.1), "end": position(end.0, end.1) },
+            "text": new_text,
+        });
+        apply_content_change(&mut text, &change);
+        text
+    }
+
+    #[test]
+    fn columns_count_utf16_units() {
+        // "é" ocupa 2 bytes e 1 unidade; "😀" ocupa 4 bytes e 2 unidades
+        let text = "é😀x\n";
+        assert_eq!(byte_offset(text, &position(0, 1)), 2);
+        assert_eq!(byte_offset(text, &position(0, 3)), 6);
+        assert_eq!(edited(text, (0, 1), (0, 3), "a"), "éax\n");
+    }
+
+    #[test]
+    fn positions_past_the_end_are_clamped() {
+        let text = "ab\ncd";
+        assert_eq!(byte_offset(text, &position(0, 10)), 2);
+        assert_eq!(byte_offset(text, &position(5, 0)), text.len());
+        assert_eq!(edited(text, (1, 1), (9, 9), "X"), "ab\ncX");
+    }
+
+    #[test]
+    fn crlf_lines_end_before_the_carriage_return() {
+        let text = "ab\r\ncd\r\n";
+        assert_eq!(byte_offset(text, &position(1, 0)), 4);
+        assert_eq!(byte_offset(text, &position(0, 10)), 2);
+        assert_eq!(edited(text, (1, 2), (1, 2), "e"), "ab\r\ncde\r\n");
+    }
+
+    #[test]
+    fn inserts_at_start_and_end() {
+        assert_eq!(edited("fn a() {}", (0, 0), (0, 0), "pub "), "pub fn a() {}");
+        assert_eq!(edited("a\n", (1, 0), (1, 0), "b\n"), "a\nb\n");
+    }
+
+    #[test]
+    fn change_without_range_replaces_everything() {
+        let mut text = "old".to_string();
+        apply_content_change(&mut text, &json!({ "text": "new" }));
+        assert_eq!(text, "new");
+    }
+}
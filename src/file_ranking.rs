@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use crate::FilePriority;
+use crate::imports::{SourceSet, resolve_imports, supports_imports};
+
+/// Nomes (sem extensão) dos ficheiros que costumam ser pontos de entrada
+const ENTRY_POINT_STEMS: [&str; 9] = [
+    "main", "lib", "index", "app", "server", "cli", "__main__", "manage", "program",
+];
+
+/// Ficheiros que definem um projeto ou pacote
+const MANIFEST_FILES: [&str; 12] = [
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "composer.json",
+    "Gemfile",
+    "Package.swift",
+    "CMakeLists.txt",
+];
+
+/// Commits recentes consultados para saber que ficheiros mudaram há pouco
+const GIT_RECENT_COMMITS: usize = 200;
+
+/// Bytes iniciais lidos de cada ficheiro à procura de imports e definições
+const HEAD_SCAN_BYTES: u64 = 16 * 1024;
+
+/// Palavras que, entre as primeiras de uma linha, indicam a definição de um símbolo
+const DEFINITION_KEYWORDS: [&str; 15] = [
+    "fn",
+    "def",
+    "class",
+    "function",
+    "func",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+    "impl",
+    "type",
+    "mod",
+    "module",
+    "object",
+    "protocol",
+];
+
+/// Palavras iniciais consideradas antes da palavra da definição (`pub async fn`)
+const DEFINITION_PREFIX_WORDS: usize = 3;
+
+/// Peso de cada sinal na pontuação de relevância
+const ENTRY_POINT_WEIGHT: f64 = 6.0;
+const MANIFEST_WEIGHT: f64 = 4.0;
+const MANIFEST_PROXIMITY_WEIGHT: f64 = 3.0;
+const PRIMARY_LANGUAGE_WEIGHT: f64 = 2.0;
+const DEPTH_PENALTY: f64 = 0.5;
+const GIT_RECENCY_WEIGHT: f64 = 3.0;
+const MAX_SYMBOLS_SCORE: f64 = 3.0;
+const INBOUND_IMPORTS_WEIGHT: f64 = 2.0;
+const MAX_INBOUND_IMPORTS_SCORE: f64 = 6.0;
+
+/// Escolhe os `max_files` candidatos mais relevantes. A pontuação soma os pontos de
+/// entrada, a proximidade a um manifesto, a pouca profundidade, as alterações recentes
+/// no git, as definições no início de cada ficheiro e o número de ficheiros que o
+/// importam. As definições são contadas no texto, e não no índice, para que a
+/// seleção não dependa de o índice já estar preenchido. Se o `deadline` for atingido,
+/// os restantes ficheiros não são lidos.
+pub(crate) fn most_relevant(
+    root: &Path,
+    candidates: Vec<(PathBuf, FilePriority)>,
+    max_files: usize,
+    deadline: Option<Instant>,
+) -> Vec<(PathBuf, FilePriority)> {
+    let relative: Vec<PathBuf> = candidates
+        .iter()
+        .map(|(path, _)| path.strip_prefix(root).unwrap_or(path).to_path_buf())
+        .collect();
+    let recency = git_recency(root);
+    let heads = scan_heads(root, &relative, deadline);
+    let mut manifest_dirs: HashMap<PathBuf, bool> = HashMap::new();
+
+    let scores: Vec<f64> = candidates
+        .iter()
+        .zip(&relative)
+        .map(|((_, priority), relative)| {
+            let mut score = 0.0;
+            if is_entry_point(relative) {
+                score += ENTRY_POINT_WEIGHT;
+            }
+            if is_manifest(relative) {
+                score += MANIFEST_WEIGHT;
+            } else if let Some(distance) = manifest_distance(root, relative, &mut manifest_dirs) {
+                score += MANIFEST_PROXIMITY_WEIGHT / (1 + distance) as f64;
+            }
+            if *priority == FilePriority::Primary {
+                score += PRIMARY_LANGUAGE_WEIGHT;
+            }
+            score -= DEPTH_PENALTY * relative.components().count().saturating_sub(1) as f64;
+
+            if let Some(commit) = recency.get(relative) {
+                score += GIT_RECENCY_WEIGHT * (1.0 - *commit as f64 / GIT_RECENT_COMMITS as f64);
+            }
+            if let Some(definitions) = heads.definitions.get(relative) {
+                score += (1.0 + *definitions as f64).ln().min(MAX_SYMBOLS_SCORE);
+            }
+            if let Some(count) = heads.inbound.get(relative) {
+                score += (INBOUND_IMPORTS_WEIGHT * (1.0 + *count as f64).ln())
+                    .min(MAX_INBOUND_IMPORTS_SCORE);
+            }
+            score
+        })
+        .collect();
+
+    // Empates resolvidos pelo caminho para que a seleção seja estável
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|a, b| {
+        scores[*b]
+            .total_cmp(&scores[*a])
+            .then_with(|| relative[*a].cmp(&relative[*b]))
+    });
+    let mut selected = vec![false; candidates.len()];
+    for position in order.into_iter().take(max_files) {
+        selected[position] = true;
+    }
+
+    candidates
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(candidate, selected)| selected.then_some(candidate))
+        .collect()
+}
+
+fn is_entry_point(relative: &Path) -> bool {
+    relative
+        .file_stem()
+        .is_some_and(|stem| ENTRY_POINT_STEMS.contains(&stem.to_string_lossy().as_ref()))
+}
+
+fn is_manifest(relative: &Path) -> bool {
+    relative
+        .file_name()
+        .is_some_and(|name| MANIFEST_FILES.contains(&name.to_string_lossy().as_ref()))
+}
+
+/// Número de diretorias entre o ficheiro e o manifesto mais próximo acima dele
+fn manifest_distance(
+    root: &Path,
+    relative: &Path,
+    manifest_dirs: &mut HashMap<PathBuf, bool>,
+) -> Option<usize> {
+    relative.ancestors().skip(1).position(|dir| {
+        *manifest_dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            MANIFEST_FILES
+                .iter()
+                .any(|manifest| root.join(dir).join(manifest).is_file())
+        })
+    })
+}
+
+/// Posição do commit mais recente (0 = último) que alterou cada ficheiro, entre os
+/// últimos `GIT_RECENT_COMMITS`. Vazio fora de um repositório git.
+fn git_recency(root: &Path) -> HashMap<PathBuf, usize> {
+    let mut recency = HashMap::new();
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["-c", "core.quotepath=off", "log"])
+        .arg(format!("-n{}", GIT_RECENT_COMMITS))
+        .args(["--format=%x00", "--name-only", "--relative", "--no-renames"])
+        .output();
+    let Ok(output) = output else {
+        return recency;
+    };
+    if !output.status.success() {
+        return recency;
+    }
+
+    // Cada commit começa com uma linha só com NUL, seguida dos ficheiros alterados
+    let mut commits: usize = 0;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if line.starts_with('\0') {
+            commits += 1;
+        } else if !line.is_empty() {
+            recency
+                .entry(PathBuf::from(line))
+                .or_insert(commits.saturating_sub(1));
+        }
+    }
+    recency
+}
+
+/// O que se sabe de cada candidato depois de ler o início dos ficheiros
+#[derive(Default)]
+struct HeadScan {
+    /// Número de candidatos que importam cada ficheiro
+    inbound: HashMap<PathBuf, usize>,
+    /// Linhas que parecem definir um símbolo, por ficheiro
+    definitions: HashMap<PathBuf, usize>,
+}
+
+/// Lê apenas o início de cada candidato, contando os imports e as definições
+fn scan_heads(root: &Path, relative: &[PathBuf], deadline: Option<Instant>) -> HeadScan {
+    let sources = SourceSet::new(relative);
+    let mut scan = HeadScan::default();
+
+    for path in relative {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        let Some(head) = read_head(&root.join(path)) else {
+            continue;
+        };
+        scan.definitions
+            .insert(path.clone(), count_definitions(&head));
+        if !supports_imports(path) {
+            continue;
+        }
+        for import in resolve_imports(root, path, &head, &sources) {
+            if import != *path {
+                *scan.inbound.entry(import).or_insert(0) += 1;
+            }
+        }
+    }
+    scan
+}
+
+/// Linhas em que uma palavra de definição (`fn`, `class`, `def`, ...) aparece entre
+/// as primeiras palavras, depois de modificadores como `pub`, `export` ou `async`.
+/// Linhas de comentário não contam.
+fn count_definitions(head: &str) -> usize {
+    head.lines()
+        .map(str::trim_start)
+        .filter(|line| {
+            !["//", "/*", "*", "#", "--"]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .filter(|line| {
+            line.split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| !word.is_empty())
+                .take(DEFINITION_PREFIX_WORDS)
+                .any(|word| DEFINITION_KEYWORDS.contains(&word))
+        })
+        .count()
+}
+
+fn read_head(path: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(HEAD_SCAN_BYTES)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_are_counted_after_modifiers() {
+        let head = "use std::fs;\n\
+                    pub async fn load() {}\n\
+                    export default class App {}\n\
+                    def run(self):\n\
+                    // the fn keyword deep in a comment is not a definition\n\
+                    let type_name = 1;\n";
+        assert_eq!(count_definitions(head), 3);
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Ficheiros do workspace indexados pelo nome, para resolver imports sem percorrer
+/// a lista inteira em cada especificador
+pub(crate) struct SourceSet {
+    by_name: HashMap<OsString, Vec<PathBuf>>,
+}
+
+impl SourceSet {
+    pub(crate) fn new<'a>(sources: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let mut by_name: HashMap<OsString, Vec<PathBuf>> = HashMap::new();
+        for source in sources {
+            if let Some(name) = source.file_name() {
+                by_name
+                    .entry(name.to_os_string())
+                    .or_default()
+                    .push(source.clone());
+            }
+        }
+        SourceSet { by_name }
+    }
+
+    /// Ficheiro igual ao candidato ou terminado nele (`app/user.py` -> `src/app/user.py`)
+    fn find(&self, candidate: &Path) -> Option<&PathBuf> {
+        let same_name = self.by_name.get(candidate.file_name()?)?;
+        same_name
+            .iter()
+            .find(|source| *source == candidate)
+            .or_else(|| same_name.iter().find(|source| source.ends_with(candidate)))
+    }
+}
+
+/// Forma dos imports de uma linguagem
+enum ImportSyntax {
+    Rust,
+    Python,
+    JavaScript,
+}
+
+fn import_syntax(file_path: &Path) -> Option<ImportSyntax> {
+    let extension = file_path.extension()?.to_str()?;
+    match extension {
+        "rs" => Some(ImportSyntax::Rust),
+        "py" | "pyi" => Some(ImportSyntax::Python),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "vue" | "svelte" => {
+            Some(ImportSyntax::JavaScript)
+        }
+        _ => None,
+    }
+}
+
+/// Verifica se os imports do ficheiro podem ser resolvidos
+pub(crate) fn supports_imports(file_path: &Path) -> bool {
+    import_syntax(file_path).is_some()
+}
+
+/// Ficheiros do workspace importados pelo ficheiro, pela ordem dos imports.
+/// Os caminhos são relativos ao workspace.
+pub(crate) fn resolve_imports(
+    workspace_dir: &Path,
+    file_path: &Path,
+    content: &str,
+    sources: &SourceSet,
+) -> Vec<PathBuf> {
+    let candidates = match import_syntax(file_path) {
+        Some(ImportSyntax::Rust) => rust_import_candidates(workspace_dir, file_path, content),
+        Some(ImportSyntax::Python) => python_import_candidates(file_path, content),
+        Some(ImportSyntax::JavaScript) => javascript_import_candidates(file_path, content),
+        None => return Vec::new(),
+    };
+
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    for group in candidates {
+        let found = group
+            .iter()
+            .find_map(|candidate| sources.find(&normalize(candidate)));
+        if let Some(found) = found
+            && !resolved.contains(found)
+        {
+            resolved.push(found.clone());
+        }
+    }
+    resolved
+}
+
+/// `import x from './user'`, `require('../src/user')`: caminhos relativos com as
+/// extensões e ficheiros `index` habituais
+fn javascript_import_candidates(file_path: &Path, content: &str) -> Vec<Vec<PathBuf>> {
+    let dir = file_path.parent().unwrap_or(Path::new(""));
+    let extensions = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+    quoted_specifiers(content)
+        .into_iter()
+        .filter_map(|specifier| {
+            let base = if specifier.starts_with('.') {
+                dir.join(&specifier)
+            } else if let Some(rest) = specifier.strip_prefix("@/") {
+                Path::new("src").join(rest)
+            } else {
+                return None;
+            };
+            let mut group = vec![base.clone()];
+            for ext in extensions {
+                group.push(PathBuf::from(format!("{}.{}", base.display(), ext)));
+            }
+            for ext in extensions {
+                group.push(base.join(format!("index.{}", ext)));
+            }
+            Some(group)
+        })
+        .collect()
+}
+
+/// Especificadores de `from '...'`, `import '...'` e `require('...')`
+fn quoted_specifiers(content: &str) -> Vec<String> {
+    let mut specifiers = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let rest = if let Some(index) = trimmed.find("require(") {
+            &trimmed[index + "require(".len()..]
+        } else if trimmed.starts_with("import") || trimmed.starts_with("export") {
+            match trimmed.rfind(" from ") {
+                Some(index) => &trimmed[index + " from ".len()..],
+                None => trimmed.trim_start_matches("import").trim_start(),
+            }
+        } else if let Some(index) = trimmed.find("} from ") {
+            &trimmed[index + "} from ".len()..]
+        } else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let Some(quote) = rest
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '\'' | '"' | '`'))
+        else {
+            continue;
+        };
+        if let Some(specifier) = rest[1..].split(quote).next() {
+            specifiers.push(specifier.to_string());
+        }
+    }
+    specifiers
+}
+
+/// `from app.user import X`, `import app.user`, `from .user import X`
+fn python_import_candidates(file_path: &Path, content: &str) -> Vec<Vec<PathBuf>> {
+    let dir = file_path.parent().unwrap_or(Path::new(""));
+    let mut groups = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        let (module, names) = if let Some(rest) = trimmed.strip_prefix("from ") {
+            let mut parts = rest.splitn(2, " import ");
+            let module = parts.next().unwrap_or_default().trim();
+            let names: Vec<&str> = parts
+                .next()
+                .unwrap_or_default()
+                .trim_matches(|c| c == '(' || c == ')')
+                .split(',')
+                .map(|name| name.split_whitespace().next().unwrap_or_default())
+                .filter(|name| !name.is_empty())
+                .collect();
+            (module, names)
+        } else if let Some(rest) = trimmed.strip_prefix("import ") {
+            (
+                rest.split([',', ' ']).next().unwrap_or_default(),
+                Vec::new(),
+            )
+        } else {
+            continue;
+        };
+        if module.is_empty() {
+            continue;
+        }
+
+        // Imports relativos partem da diretoria do ficheiro
+        let dots = module.chars().take_while(|c| *c == '.').count();
+        let mut base = PathBuf::new();
+        if dots > 0 {
+            base = dir.to_path_buf();
+            for _ in 1..dots {
+                base = base.parent().map(Path::to_path_buf).unwrap_or_default();
+            }
+        }
+        let module_path = base.join(module[dots..].replace('.', "/"));
+
+        // `from pacote import modulo` também pode referir o ficheiro `pacote/modulo.py`
+        for name in names {
+            let name_path = module_path.join(name);
+            groups.push(vec![
+                name_path.with_extension("py"),
+                name_path.join("__init__.py"),
+            ]);
+        }
+        groups.push(vec![
+            PathBuf::from(format!("{}.py", module_path.display())),
+            module_path.join("__init__.py"),
+        ]);
+    }
+
+    groups
+}
+
+/// `use nome_da_crate::a::b` e `use crate::a::b` (testes de integração e módulos Rust),
+/// resolvidos para `src/a/b.rs`, `src/a/b/mod.rs`, `src/a.rs`, ...
+fn rust_import_candidates(
+    workspace_dir: &Path,
+    file_path: &Path,
+    content: &str,
+) -> Vec<Vec<PathBuf>> {
+    // A crate é a diretoria mais próxima com Cargo.toml
+    let Some(crate_dir) = file_path
+        .ancestors()
+        .skip(1)
+        .find(|dir| workspace_dir.join(dir).join("Cargo.toml").is_file())
+    else {
+        return Vec::new();
+    };
+    let crate_name = fs::read_to_string(workspace_dir.join(crate_dir).join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| {
+            manifest.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "name").then(|| value.trim().trim_matches('"').replace('-', "_"))
+            })
+        })
+        .unwrap_or_default();
+    let src = crate_dir.join("src");
+
+    let mut groups = Vec::new();
+    for line in content.lines() {
+        let Some(path) = line.trim().strip_prefix("use ") else {
+            continue;
+        };
+        let segments: Vec<&str> = path
+            .split("::")
+            .map(|segment| segment.trim_end_matches(';').trim())
+            .take_while(|segment| {
+                !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+            .collect();
+        let Some((first, rest)) = segments.split_first() else {
+            continue;
+        };
+        if *first != crate_name && *first != "crate" {
+            continue;
+        }
+
+        let mut group = Vec::new();
+        for len in (1..=rest.len()).rev() {
+            let module = src.join(rest[..len].join("/"));
+            group.push(module.with_extension("rs"));
+            group.push(module.join("mod.rs"));
+        }
+        group.push(src.join("lib.rs"));
+        groups.push(group);
+    }
+
+    groups
+}
+
+/// Remove componentes `.` e `..` de um caminho relativo
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
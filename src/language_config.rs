@@ -0,0 +1,296 @@
+use anyhow::{Result, anyhow};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tree_sitter::{Language, Parser};
+
+use crate::symbol_index::fnv1a;
+
+/// Diretoria de configuração do projeto, relativa à raiz do workspace
+pub(crate) const PROJECT_CONFIG_DIR: &str = ".workspace-context";
+
+/// Query definida pelo utilizador para uma linguagem
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CustomQuery {
+    pub(crate) source: String,
+    /// Com `; override` na primeira linha, a query substitui a embutida em vez de a estender
+    pub(crate) replaces_builtin: bool,
+    /// Hash da query, calculado ao carregar, que identifica a query compilada em cache
+    pub(crate) fingerprint: u64,
+}
+
+/// Gramática carregada em runtime a partir de uma biblioteca partilhada
+#[derive(Debug, Clone)]
+pub(crate) struct DynamicGrammar {
+    pub(crate) name: String,
+    extensions: Vec<String>,
+    pub(crate) language: Language,
+    pub(crate) query: String,
+    /// Hash da query, como em `CustomQuery`
+    pub(crate) fingerprint: u64,
+}
+
+/// Entrada de `grammars` no `config.json`
+#[derive(Debug, Deserialize)]
+struct GrammarEntry {
+    /// Biblioteca partilhada da gramática (`.so`/`.dylib`), ex.: a compilada pelo Neovim
+    library: String,
+    /// Ficheiro `.scm` com a query de símbolos (`@kind.name`)
+    query: String,
+    extensions: Vec<String>,
+    /// Função exportada pela biblioteca (padrão: `tree_sitter_<nome>`)
+    symbol: Option<String>,
+}
+
+/// Conteúdo de `config.json`
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    grammars: BTreeMap<String, GrammarEntry>,
+}
+
+/// Personalizações da extração de símbolos carregadas do utilizador e do projeto
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LanguageConfig {
+    /// Queries extra por linguagem (`rust`, `typescript`, ...), pelo nome do ficheiro `.scm`
+    queries: HashMap<String, CustomQuery>,
+    /// Gramáticas externas, só da configuração do utilizador
+    grammars: Vec<DynamicGrammar>,
+}
+
+impl LanguageConfig {
+    /// Carrega as queries de `~/.config/workspace-context/` e depois de
+    /// `<workspace>/.workspace-context/`, aplicando as do projeto por último.
+    /// As gramáticas só vêm da configuração do utilizador: carregar uma biblioteca
+    /// indicada por um repositório clonado executaria código desse repositório.
+    pub(crate) fn load(workspace_dir: &Path) -> Self {
+        let mut config = LanguageConfig::default();
+
+        if let Some(user_dir) = user_config_dir() {
+            config.load_queries(&user_dir.join("queries"));
+            config.load_grammars(&user_dir);
+        }
+
+        let project_dir = workspace_dir.join(PROJECT_CONFIG_DIR);
+        config.load_queries(&project_dir.join("queries"));
+        warn_project_grammars(&project_dir);
+
+        for query in config.queries.values_mut() {
+            query.fingerprint =
+                fnv1a(format!("{}\n{}", query.replaces_builtin, query.source).as_bytes());
+        }
+        config
+    }
+
+    /// Lê `grammars` do `config.json`; caminhos relativos são resolvidos a partir
+    /// da diretoria do ficheiro. Gramáticas inválidas são ignoradas com um aviso.
+    fn load_grammars(&mut self, config_dir: &Path) {
+        let config_path = config_dir.join("config.json");
+        let Ok(content) = fs::read_to_string(&config_path) else {
+            return;
+        };
+        let config_file: ConfigFile = match serde_json::from_str(&content) {
+            Ok(config_file) => config_file,
+            Err(error) => {
+                eprintln!("⚠️  Invalid {}: {}", config_path.display(), error);
+                return;
+            }
+        };
+
+        for (name, entry) in config_file.grammars {
+            match load_grammar(config_dir, &name, &entry) {
+                Ok(grammar) => {
+                    self.grammars
+                        .retain(|existing| existing.name != grammar.name);
+                    self.grammars.push(grammar);
+                }
+                Err(error) => eprintln!("⚠️  Skipping grammar {}: {}", name, error),
+            }
+        }
+    }
+
+    fn load_queries(&mut self, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "scm"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let (Some(language), Ok(source)) = (
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_lowercase()),
+                fs::read_to_string(&path),
+            ) else {
+                continue;
+            };
+
+            let entry = self.queries.entry(language).or_default();
+            if is_override(&source) {
+                *entry = CustomQuery {
+                    source,
+                    replaces_builtin: true,
+                    fingerprint: 0,
+                };
+            } else {
+                entry.source.push('\n');
+                entry.source.push_str(&source);
+            }
+        }
+    }
+
+    /// Gramática externa configurada para a extensão do ficheiro
+    pub(crate) fn grammar_for_path(&self, file_path: &Path) -> Option<&DynamicGrammar> {
+        let extension = file_path.extension()?.to_string_lossy().to_lowercase();
+        self.grammars.iter().find(|grammar| {
+            grammar.extensions.iter().any(|candidate| {
+                candidate
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(&extension)
+            })
+        })
+    }
+
+    /// Query definida pelo utilizador para a linguagem, se existir
+    pub(crate) fn custom_query(&self, language: &str) -> Option<&CustomQuery> {
+        self.queries.get(language)
+    }
+
+    /// Impressão digital das queries e gramáticas, para invalidar símbolos em cache
+    /// quando a configuração muda
+    pub(crate) fn fingerprint(&self) -> u64 {
+        let mut languages: Vec<&String> = self.queries.keys().collect();
+        languages.sort();
+
+        let mut description = String::new();
+        for language in languages {
+            let query = &self.queries[language];
+            description.push_str(&format!(
+                "query {} {}\n{}\n",
+                language, query.replaces_builtin, query.source
+            ));
+        }
+        for grammar in &self.grammars {
+            description.push_str(&format!(
+                "grammar {} {}\n{}\n",
+                grammar.name,
+                grammar.extensions.join(","),
+                grammar.query
+            ));
+        }
+
+        fnv1a(description.as_bytes())
+    }
+}
+
+/// Avisa que as gramáticas do `config.json` do projeto são ignoradas
+fn warn_project_grammars(project_dir: &Path) {
+    let config_path = project_dir.join("config.json");
+    let Ok(content) = fs::read_to_string(&config_path) else {
+        return;
+    };
+    if let Ok(config_file) = serde_json::from_str::<ConfigFile>(&content)
+        && !config_file.grammars.is_empty()
+    {
+        eprintln!(
+            "⚠️  Ignoring grammars in {}: grammars are only loaded from the user config",
+            config_path.display()
+        );
+    }
+}
+
+/// Carrega a biblioteca e a query de uma gramática externa
+fn load_grammar(config_dir: &Path, name: &str, entry: &GrammarEntry) -> Result<DynamicGrammar> {
+    let library = resolve_path(config_dir, &entry.library);
+    let query_path = resolve_path(config_dir, &entry.query);
+    let symbol = entry
+        .symbol
+        .clone()
+        .unwrap_or_else(|| format!("tree_sitter_{}", name.replace('-', "_")));
+
+    let language = load_language(&library, &symbol)?;
+    let query = fs::read_to_string(&query_path)
+        .map_err(|error| anyhow!("cannot read {}: {}", query_path.display(), error))?;
+
+    Ok(DynamicGrammar {
+        name: name.to_string(),
+        extensions: entry.extensions.clone(),
+        language,
+        fingerprint: fnv1a(query.as_bytes()),
+        query,
+    })
+}
+
+/// Abre a biblioteca partilhada e obtém a `Language` exportada por `symbol`.
+/// Cada biblioteca é carregada uma única vez por processo e nunca é descarregada,
+/// porque a `Language` aponta para dados estáticos dentro dela.
+fn load_language(library_path: &Path, symbol: &str) -> Result<Language> {
+    static LOADED: OnceLock<Mutex<HashMap<(PathBuf, String), Language>>> = OnceLock::new();
+
+    let key = (library_path.to_path_buf(), symbol.to_string());
+    let mut loaded = LOADED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(language) = loaded.get(&key) {
+        return Ok(*language);
+    }
+
+    // SAFETY: a biblioteca é uma gramática tree-sitter cuja função `symbol` não recebe
+    // argumentos e devolve um `*const TSLanguage`, que `Language` representa de forma
+    // transparente. A biblioteca é mantida carregada até ao fim do processo.
+    let language = unsafe {
+        let library = libloading::Library::new(library_path)?;
+        let language = {
+            let constructor: libloading::Symbol<unsafe extern "C" fn() -> Language> =
+                library.get(symbol.as_bytes())?;
+            constructor()
+        };
+        std::mem::forget(library);
+        language
+    };
+
+    // Rejeitar gramáticas geradas para uma versão de ABI incompatível
+    Parser::new().set_language(language)?;
+
+    loaded.insert(key, language);
+    Ok(language)
+}
+
+/// Resolve `~/` e caminhos relativos à diretoria de configuração
+fn resolve_path(config_dir: &Path, path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = env::var_os("HOME")
+    {
+        return PathBuf::from(home).join(rest);
+    }
+    config_dir.join(path)
+}
+
+/// Diretoria de configuração do utilizador (`$XDG_CONFIG_HOME/workspace-context`)
+fn user_config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("workspace-context"))
+}
+
+/// A primeira linha não vazia é o comentário `; override`
+fn is_override(source: &str) -> bool {
+    source
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| {
+            line.starts_with(';') && line.trim_start_matches(';').trim() == "override"
+        })
+}
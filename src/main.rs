@@ -2,20 +2,51 @@ use anyhow::Result;
 use jsonrpc_stdio_server::jsonrpc_core::{
     Error, IoHandler, Params, Result as JsonRpcResult, Value,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, json};
-use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::{self, BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use tree_sitter::{Parser, Query, QueryCursor};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, Tree};
 use walkdir::WalkDir;
 
+use language_config::{CustomQuery, LanguageConfig};
+use symbol_index::SymbolIndex;
+use workspace_watcher::{Workspace, Workspaces};
+
+mod build_files;
+mod config_outline;
+mod content_filter;
+mod documents;
+mod file_ranking;
+mod imports;
+mod language_config;
+mod markdown;
+mod relevant_context;
+mod search_terms;
+mod semantic_index;
+mod sfc;
+mod sql_schema;
+mod symbol_diff;
+mod symbol_index;
+mod syntax_trees;
+mod test_map;
+mod token_budget;
+mod workspace_watcher;
+
 /// Estrutura principal que contém a lógica do servidor MCP
-struct RpcHandler;
+struct RpcHandler {
+    /// Workspaces observados, partilhados entre os handlers de cada método
+    workspaces: Arc<Workspaces>,
+}
 
 impl RpcHandler {
-    fn new() -> Self {
-        RpcHandler
+    fn new(workspaces: Arc<Workspaces>) -> Self {
+        RpcHandler { workspaces }
     }
 
     /// Implementa o método `initialize` do protocolo MCP
@@ -35,7 +66,9 @@ impl RpcHandler {
     }
 
     /// Implementa o método `list_tools` do protocolo MCP
-    /// Retorna a definição da nossa única ferramenta
+    /// Retorna a definição das ferramentas do servidor: contexto do workspace, visão geral
+    /// do projeto, diferenças de símbolos, esquema da base de dados, testes, contexto
+    /// relevante para uma pergunta e pesquisa semântica
     fn list_tools(&self, _params: Params) -> JsonRpcResult<Value> {
         let tools = json!({
             "tools": [
@@ -68,8 +101,174 @@ impl RpcHandler {
                                 "type": "boolean",
                                 "description": "Se true, retorna apenas um resumo estatístico sem símbolos detalhados (padrão: false)",
                                 "default": false
+                            },
+                            "timeout_ms": {
+                                "type": "integer",
+                                "description": "Tempo máximo de análise em milissegundos. Ao ser atingido, retorna a árvore parcial construída até então, marcada como truncada (padrão: sem limite)",
+                                "minimum": 1
+                            },
+                            "max_file_bytes": {
+                                "type": "integer",
+                                "description": "Ficheiros maiores do que este tamanho aparecem na árvore sem serem analisados (padrão: 1048576)",
+                                "default": 1048576,
+                                "minimum": 1
+                            },
+                            "max_tokens": {
+                                "type": "integer",
+                                "description": "Tamanho máximo aproximado da árvore em tokens. Com orçamento suficiente inclui resumos da documentação; senão omite-os, depois os símbolos menos importantes e por fim reduz diretorias a contagens, indicando o que foi omitido (padrão: sem limite)",
+                                "minimum": 1
+                            }
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "get_project_overview",
+                    "description": "Retorna o título, os parágrafos iniciais e as secções do README do workspace, mais o índice de cabeçalhos dos ficheiros em docs/. Útil para conhecer as convenções do projeto.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
+                            },
+                            "max_paragraphs": {
+                                "type": "number",
+                                "description": "Número máximo de parágrafos introdutórios do README a incluir (padrão: 2)",
+                                "default": 2
+                            }
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "get_symbol_diff",
+                    "description": "Compara o workspace com uma referência git e lista os símbolos (funções, tipos, métodos) adicionados, removidos ou modificados, em vez dos hunks do patch.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "base_ref": {
+                                "type": "string",
+                                "description": "Referência git base para comparação (branch, tag ou commit; padrão: HEAD)",
+                                "default": "HEAD"
+                            },
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
+                            }
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "get_database_schema",
+                    "description": "Extrai o esquema da base de dados a partir dos ficheiros .sql: aplica as migrações por ordem e retorna as tabelas (colunas e tipos), índices, vistas e funções resultantes.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
+                            },
+                            "migrations_path": {
+                                "type": "string",
+                                "description": "Diretoria (relativa ao workspace) com os ficheiros SQL a aplicar. Se não fornecida, usa as diretorias migrations/ encontradas ou, na sua falta, todos os ficheiros .sql"
+                            }
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "get_tests",
+                    "description": "Lista os testes do workspace (funções #[test] e tests/ em Rust, describe/it/test em JS/TS, test_* e unittest.TestCase em Python) e associa cada ficheiro de testes ao módulo fonte que provavelmente cobre, pelo nome e pelos imports.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
+                            },
+                            "source_path": {
+                                "type": "string",
+                                "description": "Ficheiro fonte (relativo ao workspace) cujos testes se pretendem. Se não fornecido, lista todos os testes"
+                            }
+                        },
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "get_relevant_context",
+                    "description": "Responde a uma pergunta em linguagem natural com a parte relevante do workspace: ordena ficheiros e símbolos por BM25 sobre caminhos, nomes, comentários de documentação e identificadores (separando camelCase e snake_case) e inclui excertos de código até ao limite de tokens.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "question": {
+                                "type": "string",
+                                "description": "Pergunta ou descrição do que se procura (ex.: \"How does user creation work?\")"
+                            },
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
+                            },
+                            "max_files": {
+                                "type": "number",
+                                "description": "Número máximo de ficheiros relevantes a mostrar (padrão: 10)",
+                                "default": 10
+                            },
+                            "max_tokens": {
+                                "type": "integer",
+                                "description": "Tamanho máximo aproximado da resposta em tokens; os excertos que não cabem são omitidos (padrão: 4000)",
+                                "default": 4000,
+                                "minimum": 1
+                            },
+                            "timeout_ms": {
+                                "type": "integer",
+                                "description": "Tempo máximo de análise em milissegundos. Ao ser atingido, a pesquisa usa apenas os ficheiros já analisados e é marcada como truncada (padrão: sem limite)",
+                                "minimum": 1
+                            },
+                            "max_file_bytes": {
+                                "type": "integer",
+                                "description": "Ficheiros maiores do que este tamanho não são analisados nem pesquisados (padrão: 1048576)",
+                                "default": 1048576,
+                                "minimum": 1
+                            }
+                        },
+                        "required": ["question"],
+                        "additionalProperties": false
+                    }
+                },
+                {
+                    "name": "semantic_search",
+                    "description": "Procura os símbolos mais próximos de uma descrição, mesmo com nomes diferentes dos usados na pesquisa. Usa um índice semântico local (vetores TF-IDF de termos e trigramas das assinaturas, documentação e corpo de cada símbolo), criado na primeira pesquisa e guardado ao lado do índice de símbolos.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Descrição ou nome aproximado do que se procura"
+                            },
+                            "k": {
+                                "type": "number",
+                                "description": "Número de símbolos a retornar (padrão: 10)",
+                                "default": 10
+                            },
+                            "timeout_ms": {
+                                "type": "integer",
+                                "description": "Tempo máximo de análise em milissegundos. Ao ser atingido, a pesquisa usa apenas os ficheiros já analisados e é marcada como truncada (padrão: sem limite)",
+                                "minimum": 1
+                            },
+                            "max_file_bytes": {
+                                "type": "integer",
+                                "description": "Ficheiros maiores do que este tamanho não são analisados nem pesquisados (padrão: 1048576)",
+                                "default": 1048576,
+                                "minimum": 1
+                            },
+                            "workspace_path": {
+                                "type": "string",
+                                "description": "Caminho opcional para o diretório do workspace a analisar. Se não fornecido, usa o diretório atual ou diretório pai se estiver em workspace-context."
                             }
                         },
+                        "required": ["query"],
                         "additionalProperties": false
                     }
                 }
@@ -95,31 +294,10 @@ impl RpcHandler {
 
         match tool_name {
             "get_workspace_context" => {
+                let started = Instant::now();
                 let arguments = params_map.get("arguments");
 
-                // Verificar se foi especificado um workspace_path nos argumentos
-                let workspace_dir = if let Some(workspace_path) = arguments
-                    .and_then(|args| args.get("workspace_path"))
-                    .and_then(|v| v.as_str())
-                {
-                    PathBuf::from(workspace_path)
-                } else {
-                    // Tentar obter workspace_path da variável de ambiente
-                    if let Ok(env_workspace) = std::env::var("WORKSPACE_PATH") {
-                        PathBuf::from(env_workspace)
-                    } else {
-                        // Fallback: usar o diretório pai do diretório atual se estivermos em workspace-context
-                        let current_dir =
-                            std::env::current_dir().map_err(|_| Error::internal_error())?;
-                        if current_dir.file_name().and_then(|n| n.to_str())
-                            == Some("workspace-context")
-                        {
-                            current_dir.parent().unwrap_or(&current_dir).to_path_buf()
-                        } else {
-                            current_dir
-                        }
-                    }
-                };
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
                 // Extrair parâmetros configuráveis
                 let max_files = arguments
@@ -142,22 +320,101 @@ impl RpcHandler {
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
 
-                // Verificar se o diretório existe
-                if !workspace_dir.exists() {
-                    return Err(Error::invalid_params(&format!(
-                        "Workspace directory does not exist: {}",
-                        workspace_dir.display()
-                    )));
-                }
+                // Limite de tempo opcional; ao ser atingido devolve-se a árvore parcial
+                let timeout_ms = arguments
+                    .and_then(|args| args.get("timeout_ms"))
+                    .and_then(|v| v.as_u64());
+                let deadline = timeout_ms.map(|ms| started + Duration::from_millis(ms));
+
+                let max_file_bytes = arguments
+                    .and_then(|args| args.get("max_file_bytes"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+
+                // Orçamento opcional de tokens; a árvore é reduzida até caber
+                let max_tokens = arguments
+                    .and_then(|args| args.get("max_tokens"))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize);
+
+                // Estado observado do workspace: configuração, índice e ficheiros
+                let workspace = self.workspaces.get(&workspace_dir);
+                let mut workspace = workspace_watcher::lock(&workspace);
 
                 // Coletar ficheiros do projeto com limites configuráveis
-                let files = collect_project_files_with_limits(&workspace_dir, max_files, max_depth);
+                let selection = workspace.files(max_files, max_depth, deadline);
+                let (mut files, walk_timed_out) = (selection.files, selection.timed_out);
+
+                // Ficheiros demasiado grandes aparecem na árvore, mas não são analisados
+                let mut notes = AnalysisNotes {
+                    ranked_from: (selection.candidates > files.len())
+                        .then_some(selection.candidates),
+                    ..AnalysisNotes::default()
+                };
+                for file in &files {
+                    if let Ok(metadata) = fs::metadata(file)
+                        && metadata.len() > max_file_bytes
+                    {
+                        notes.skipped.insert(
+                            file.clone(),
+                            format!(
+                                "skipped: {} exceeds max_file_bytes",
+                                format_bytes(metadata.len())
+                            ),
+                        );
+                    }
+                }
+
+                // Reutilizar os símbolos em cache dos ficheiros que não mudaram
+                let to_index: Vec<PathBuf> = files
+                    .iter()
+                    .filter(|file| !notes.skipped.contains_key(*file))
+                    .cloned()
+                    .collect();
+                workspace.index_files(&to_index, deadline);
+
+                // Ficheiros binários, minificados ou gerados aparecem com o motivo
+                for file in &to_index {
+                    if let Some(reason) = workspace.index.skip_reason(file) {
+                        notes.skipped.insert(file.clone(), reason.to_string());
+                    }
+                }
+
+                // Sem tempo para todos: mostrar apenas os ficheiros já analisados
+                let unfinished = workspace.index.stats().unfinished;
+                if walk_timed_out || unfinished > 0 {
+                    files.retain(|file| {
+                        notes.skipped.contains_key(file) || workspace.index.symbols(file).is_some()
+                    });
+                }
+                notes.truncated =
+                    truncation_reason(walk_timed_out, unfinished, started.elapsed(), timeout_ms);
 
                 // Construir a representação hierárquica
                 let context = if summary_only {
-                    format_workspace_summary(&workspace_dir, &files)
+                    format_workspace_summary(&workspace_dir, &files, &workspace.index, &notes)
+                } else if let Some(max_tokens) = max_tokens {
+                    format_workspace_tree_within_budget(
+                        &workspace_dir,
+                        &files,
+                        max_symbols_per_file,
+                        &workspace.index,
+                        &mut notes,
+                        max_tokens,
+                    )
                 } else {
-                    format_workspace_tree_with_limits(&workspace_dir, &files, max_symbols_per_file)
+                    let options = TreeOptions {
+                        max_symbols_per_file,
+                        doc_summaries: false,
+                        collapse_depth: None,
+                    };
+                    format_workspace_tree_with_limits(
+                        &workspace_dir,
+                        &files,
+                        &options,
+                        &workspace.index,
+                        &notes,
+                    )
                 };
 
                 let result = json!({
@@ -170,130 +427,615 @@ impl RpcHandler {
                 });
                 Ok(result)
             }
-            _ => Err(Error::method_not_found()),
-        }
-    }
-}
+            "get_project_overview" => {
+                let arguments = params_map.get("arguments");
 
-/// Formata a saída do workspace como uma árvore hierárquica legível
-fn format_workspace_tree_with_limits(
-    root_dir: &Path,
-    files: &[PathBuf],
-    max_symbols_per_file: usize,
-) -> String {
-    use std::collections::BTreeMap;
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
-    let mut tree = BTreeMap::new();
-    let mut total_symbols = 0;
-    let mut files_with_symbols = 0;
+                let max_paragraphs = arguments
+                    .and_then(|args| args.get("max_paragraphs"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(2) as usize;
 
-    // Construir a estrutura da árvore
-    for file in files {
-        if let Ok(relative_path) = file.strip_prefix(root_dir) {
-            let components: Vec<&std::ffi::OsStr> = relative_path.iter().collect();
-            insert_into_tree(&mut tree, &components, file);
+                let overview = markdown::format_project_overview(&workspace_dir, max_paragraphs)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
 
-            // Contar símbolos para estatísticas
-            if let Ok(symbols) = extract_symbols_from_file(file) {
-                if !symbols.is_empty() {
-                    total_symbols += symbols.len();
-                    files_with_symbols += 1;
-                }
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": overview
+                        }
+                    ]
+                });
+                Ok(result)
             }
-        }
-    }
+            "get_symbol_diff" => {
+                let arguments = params_map.get("arguments");
 
-    // Construir a string formatada
-    let mut result = String::new();
-    result.push_str("📁 Workspace Analysis\n");
-    result.push_str("══════════════════════════════════\n\n");
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
-    format_tree_node_with_limits(&tree, &mut result, "", true, max_symbols_per_file);
+                let base_ref = arguments
+                    .and_then(|args| args.get("base_ref"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("HEAD");
 
-    // Adicionar estatísticas detalhadas no final
-    result.push_str(&format!(
-        "\n📊 Summary:\n\
-         • {} files analyzed (limited for performance)\n\
-         • {} files contain symbols\n\
-         • {} total symbols found\n\
-         • Max {} symbols shown per file\n\
-         • Root: {}\n",
-        files.len(),
-        files_with_symbols,
-        total_symbols,
-        max_symbols_per_file,
-        root_dir.display()
-    ));
+                let report = symbol_diff::format_symbol_diff(&workspace_dir, base_ref)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
 
-    result
-}
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": report
+                        }
+                    ]
+                });
+                Ok(result)
+            }
+            "get_database_schema" => {
+                let arguments = params_map.get("arguments");
 
-fn format_workspace_summary(root_dir: &Path, files: &[PathBuf]) -> String {
-    let mut result = String::new();
-    result.push_str("📁 Workspace Summary\n");
-    result.push_str("═══════════════════\n\n");
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
-    // Agrupar arquivos por extensão
-    let mut extensions = BTreeMap::new();
-    let mut total_symbols = 0;
-    let mut files_with_symbols = 0;
+                let migrations_path = arguments
+                    .and_then(|args| args.get("migrations_path"))
+                    .and_then(|v| v.as_str());
 
-    for file in files {
-        if let Some(ext) = file.extension() {
-            let ext_str = ext.to_string_lossy().to_lowercase();
-            *extensions.entry(ext_str).or_insert(0) += 1;
-        }
+                let schema = sql_schema::format_database_schema(&workspace_dir, migrations_path)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
 
-        // Contar símbolos
-        if let Ok(symbols) = extract_symbols_from_file(file) {
-            if !symbols.is_empty() {
-                total_symbols += symbols.len();
-                files_with_symbols += 1;
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": schema
+                        }
+                    ]
+                });
+                Ok(result)
             }
-        }
-    }
+            "get_tests" => {
+                let arguments = params_map.get("arguments");
 
-    result.push_str("📂 File Types:\n");
-    for (ext, count) in extensions.iter() {
-        result.push_str(&format!("  • .{}: {} files\n", ext, count));
-    }
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
-    result.push_str(&format!(
-        "\n📊 Statistics:\n\
-         • {} total files\n\
-         • {} files with symbols\n\
-         • {} total symbols\n\
-         • Root: {}\n",
-        files.len(),
-        files_with_symbols,
-        total_symbols,
-        root_dir.display()
-    ));
+                let source_path = arguments
+                    .and_then(|args| args.get("source_path"))
+                    .and_then(|v| v.as_str());
 
-    result
-}
+                let tests = test_map::format_tests(&workspace_dir, source_path)
+                    .map_err(|e| Error::invalid_params(e.to_string()))?;
 
-/// Estrutura para representar um nó na árvore
-#[derive(Debug)]
-struct TreeNode {
-    file_path: Option<PathBuf>,
-    children: BTreeMap<std::ffi::OsString, TreeNode>,
-}
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": tests
+                        }
+                    ]
+                });
+                Ok(result)
+            }
+            "get_relevant_context" => {
+                let started = Instant::now();
+                let arguments = params_map.get("arguments");
 
-impl TreeNode {
-    fn new() -> Self {
-        TreeNode {
-            file_path: None,
-            children: BTreeMap::new(),
-        }
-    }
-}
+                let workspace_dir = resolve_workspace_dir(arguments)?;
 
-/// Insere um ficheiro na estrutura da árvore
-fn insert_into_tree(
-    tree: &mut BTreeMap<std::ffi::OsString, TreeNode>,
-    components: &[&std::ffi::OsStr],
-    full_path: &Path,
+                let question = arguments
+                    .and_then(|args| args.get("question"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::invalid_params("Missing question"))?;
+
+                let max_files = arguments
+                    .and_then(|args| args.get("max_files"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(10) as usize;
+
+                let max_tokens = arguments
+                    .and_then(|args| args.get("max_tokens"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_CONTEXT_TOKENS) as usize;
+
+                // Limites como em get_workspace_context: tempo e tamanho dos ficheiros
+                let timeout_ms = arguments
+                    .and_then(|args| args.get("timeout_ms"))
+                    .and_then(|v| v.as_u64());
+
+                let max_file_bytes = arguments
+                    .and_then(|args| args.get("max_file_bytes"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+
+                // A pergunta é procurada nos ficheiros mais relevantes do workspace
+                let workspace = self.workspaces.get(&workspace_dir);
+                let mut workspace = workspace_watcher::lock(&workspace);
+                let (files, truncated) =
+                    index_search_files(&mut workspace, max_file_bytes, started, timeout_ms);
+
+                let context = relevant_context::format_relevant_context(
+                    &workspace_dir,
+                    question,
+                    &files,
+                    &workspace.index,
+                    max_files,
+                    max_tokens,
+                    truncated.as_deref(),
+                );
+
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": context
+                        }
+                    ]
+                });
+                Ok(result)
+            }
+            "semantic_search" => {
+                let started = Instant::now();
+                let arguments = params_map.get("arguments");
+
+                let workspace_dir = resolve_workspace_dir(arguments)?;
+
+                let query = arguments
+                    .and_then(|args| args.get("query"))
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| Error::invalid_params("Missing query"))?;
+
+                let k = arguments
+                    .and_then(|args| args.get("k"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(10) as usize;
+
+                // Limites como em get_workspace_context: tempo e tamanho dos ficheiros
+                let timeout_ms = arguments
+                    .and_then(|args| args.get("timeout_ms"))
+                    .and_then(|v| v.as_u64());
+
+                let max_file_bytes = arguments
+                    .and_then(|args| args.get("max_file_bytes"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_MAX_FILE_BYTES);
+
+                let workspace = self.workspaces.get(&workspace_dir);
+                let mut workspace = workspace_watcher::lock(&workspace);
+                let (files, truncated) =
+                    index_search_files(&mut workspace, max_file_bytes, started, timeout_ms);
+                let semantic = workspace.semantic_index(&files);
+
+                let report = semantic_index::format_semantic_search(
+                    semantic,
+                    query,
+                    k,
+                    truncated.as_deref(),
+                );
+
+                let result = json!({
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": report
+                        }
+                    ]
+                });
+                Ok(result)
+            }
+            _ => Err(Error::method_not_found()),
+        }
+    }
+}
+
+/// Seleciona e indexa os ficheiros pesquisados por `get_relevant_context` e
+/// `semantic_search`, sem os maiores do que `max_file_bytes`. Devolve os ficheiros já
+/// analisados e, se `timeout_ms` interrompeu a análise, a explicação.
+fn index_search_files(
+    workspace: &mut Workspace,
+    max_file_bytes: u64,
+    started: Instant,
+    timeout_ms: Option<u64>,
+) -> (Vec<PathBuf>, Option<String>) {
+    let deadline = timeout_ms.map(|ms| started + Duration::from_millis(ms));
+    let selection = workspace.files(SEARCH_MAX_FILES, SEARCH_MAX_DEPTH, deadline);
+    let mut files: Vec<PathBuf> = selection
+        .files
+        .into_iter()
+        .filter(|file| fs::metadata(file).is_ok_and(|metadata| metadata.len() <= max_file_bytes))
+        .collect();
+    workspace.index_files(&files, deadline);
+
+    let unfinished = workspace.index.stats().unfinished;
+    files.retain(|file| workspace.index.symbols(file).is_some());
+    let truncated = truncation_reason(
+        selection.timed_out,
+        unfinished,
+        started.elapsed(),
+        timeout_ms,
+    );
+    (files, truncated)
+}
+
+/// Explicação para quando o limite de tempo interrompeu a análise
+fn truncation_reason(
+    walk_timed_out: bool,
+    unfinished: usize,
+    elapsed: Duration,
+    timeout_ms: Option<u64>,
+) -> Option<String> {
+    let limit = timeout_ms
+        .map(|ms| format!(" (timeout_ms = {})", ms))
+        .unwrap_or_default();
+    if walk_timed_out {
+        Some(format!(
+            "file walk stopped after {} ms{}",
+            elapsed.as_millis(),
+            limit
+        ))
+    } else if unfinished > 0 {
+        Some(format!("{} files not analyzed{}", unfinished, limit))
+    } else {
+        None
+    }
+}
+
+/// Determina o diretório do workspace a partir dos argumentos, da variável de ambiente
+/// `WORKSPACE_PATH` ou do diretório atual, validando que existe
+fn resolve_workspace_dir(arguments: Option<&Value>) -> JsonRpcResult<PathBuf> {
+    // Verificar se foi especificado um workspace_path nos argumentos
+    let workspace_dir = if let Some(workspace_path) = arguments
+        .and_then(|args| args.get("workspace_path"))
+        .and_then(|v| v.as_str())
+    {
+        PathBuf::from(workspace_path)
+    } else {
+        // Tentar obter workspace_path da variável de ambiente
+        if let Ok(env_workspace) = std::env::var("WORKSPACE_PATH") {
+            PathBuf::from(env_workspace)
+        } else {
+            // Fallback: usar o diretório pai do diretório atual se estivermos em workspace-context
+            let current_dir = std::env::current_dir().map_err(|_| Error::internal_error())?;
+            if current_dir.file_name().and_then(|n| n.to_str()) == Some("workspace-context") {
+                current_dir.parent().unwrap_or(&current_dir).to_path_buf()
+            } else {
+                current_dir
+            }
+        }
+    };
+
+    // Verificar se o diretório existe
+    if !workspace_dir.exists() {
+        return Err(Error::invalid_params(format!(
+            "Workspace directory does not exist: {}",
+            workspace_dir.display()
+        )));
+    }
+
+    Ok(workspace_dir)
+}
+
+/// O que a árvore mostra; reduzido passo a passo para caber em `max_tokens`
+#[derive(Debug, Clone, Copy)]
+struct TreeOptions {
+    max_symbols_per_file: usize,
+    /// Primeira frase da documentação de cada símbolo
+    doc_summaries: bool,
+    /// Diretorias a partir desta profundidade (1 = topo) aparecem só com contagens
+    collapse_depth: Option<usize>,
+}
+
+/// Árvore que cabe em `max_tokens`. Com orçamento suficiente inclui os resumos da
+/// documentação; senão retira-os, depois retira os símbolos menos importantes e por
+/// fim reduz as diretorias mais profundas a contagens. O que foi omitido fica nas notas.
+fn format_workspace_tree_within_budget(
+    root_dir: &Path,
+    files: &[PathBuf],
+    max_symbols_per_file: usize,
+    index: &SymbolIndex,
+    notes: &mut AnalysisNotes,
+    max_tokens: usize,
+) -> String {
+    let full = TreeOptions {
+        max_symbols_per_file,
+        doc_summaries: true,
+        collapse_depth: None,
+    };
+    let mut steps = vec![
+        full,
+        TreeOptions {
+            doc_summaries: false,
+            ..full
+        },
+    ];
+    let mut limit = max_symbols_per_file;
+    while limit > 0 {
+        limit /= 2;
+        steps.push(TreeOptions {
+            max_symbols_per_file: limit,
+            doc_summaries: false,
+            collapse_depth: None,
+        });
+    }
+    let relative: Vec<&Path> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(root_dir).ok())
+        .collect();
+    let deepest_dir = relative
+        .iter()
+        .map(|path| path.components().count().saturating_sub(1))
+        .max()
+        .unwrap_or(0);
+    for collapse_depth in (1..=deepest_dir).rev() {
+        steps.push(TreeOptions {
+            max_symbols_per_file: 0,
+            doc_summaries: false,
+            collapse_depth: Some(collapse_depth),
+        });
+    }
+
+    let shown_symbols = |limit: usize| -> usize {
+        files
+            .iter()
+            .filter_map(|file| match index.symbols(file) {
+                Some(Ok(symbols)) => Some(symbols.len().min(limit)),
+                _ => None,
+            })
+            .sum()
+    };
+
+    let mut output = String::new();
+    for options in &steps {
+        let mut elided = Vec::new();
+        if !options.doc_summaries {
+            elided.push("doc summaries".to_string());
+        }
+        if options.max_symbols_per_file < max_symbols_per_file {
+            elided.push(format!(
+                "{} low-ranked symbols",
+                shown_symbols(max_symbols_per_file) - shown_symbols(options.max_symbols_per_file)
+            ));
+        }
+        if let Some(collapse_depth) = options.collapse_depth {
+            // Diretorias mostradas como contagens: as que estão nessa profundidade
+            let collapsed: std::collections::HashSet<PathBuf> = relative
+                .iter()
+                .filter(|path| path.components().count() > collapse_depth)
+                .map(|path| path.components().take(collapse_depth).collect())
+                .collect();
+            elided.push(format!(
+                "{} directories collapsed into counts",
+                collapsed.len()
+            ));
+        }
+        notes.elided = (!elided.is_empty()).then(|| elided.join(", "));
+
+        output = format_workspace_tree_with_limits(root_dir, files, options, index, notes);
+        if token_budget::estimate_tokens(&output) <= max_tokens {
+            return output;
+        }
+    }
+
+    // Nem a versão mais reduzida cabe: devolvê-la com o aviso
+    let tokens = token_budget::estimate_tokens(&output);
+    let elided = notes.elided.take().unwrap_or_default();
+    notes.elided = Some(format!(
+        "{} (still ~{} tokens for max_tokens = {})",
+        elided, tokens, max_tokens
+    ));
+    format_workspace_tree_with_limits(root_dir, files, &steps[steps.len() - 1], index, notes)
+}
+
+/// Formata a saída do workspace como uma árvore hierárquica legível
+fn format_workspace_tree_with_limits(
+    root_dir: &Path,
+    files: &[PathBuf],
+    options: &TreeOptions,
+    index: &SymbolIndex,
+    notes: &AnalysisNotes,
+) -> String {
+    use std::collections::BTreeMap;
+
+    let mut tree = BTreeMap::new();
+    let mut total_symbols = 0;
+    let mut files_with_symbols = 0;
+
+    // Construir a estrutura da árvore
+    for file in files {
+        if let Ok(relative_path) = file.strip_prefix(root_dir) {
+            let components: Vec<&std::ffi::OsStr> = relative_path.iter().collect();
+            insert_into_tree(&mut tree, &components, file);
+
+            // Contar símbolos para estatísticas
+            if let Some(Ok(symbols)) = index.symbols(file)
+                && !symbols.is_empty()
+            {
+                total_symbols += symbols.len();
+                files_with_symbols += 1;
+            }
+        }
+    }
+
+    // Construir a string formatada
+    let mut result = String::new();
+    result.push_str("📁 Workspace Analysis\n");
+    result.push_str("══════════════════════════════════\n\n");
+    result.push_str(&format_truncation_marker(notes));
+
+    format_tree_node_with_limits(&tree, &mut result, "", 1, options, index, notes);
+
+    // Adicionar estatísticas detalhadas no final
+    result.push_str(&format!(
+        "\n📊 Summary:\n\
+         • {} files analyzed (limited for performance)\n\
+         • {} files contain symbols\n\
+         • {} total symbols found\n\
+         • Max {} symbols shown per file\n\
+         • {}\n\
+         {}\
+         • Root: {}\n",
+        files.len(),
+        files_with_symbols,
+        total_symbols,
+        options.max_symbols_per_file,
+        format_index_stats(index),
+        format_analysis_notes(notes),
+        root_dir.display()
+    ));
+
+    result
+}
+
+fn format_workspace_summary(
+    root_dir: &Path,
+    files: &[PathBuf],
+    index: &SymbolIndex,
+    notes: &AnalysisNotes,
+) -> String {
+    let mut result = String::new();
+    result.push_str("📁 Workspace Summary\n");
+    result.push_str("═══════════════════\n\n");
+    result.push_str(&format_truncation_marker(notes));
+
+    // Agrupar arquivos por extensão
+    let mut extensions = BTreeMap::new();
+    let mut total_symbols = 0;
+    let mut files_with_symbols = 0;
+
+    for file in files {
+        if let Some(ext) = file.extension() {
+            let ext_str = ext.to_string_lossy().to_lowercase();
+            *extensions.entry(ext_str).or_insert(0) += 1;
+        }
+
+        // Contar símbolos
+        if let Some(Ok(symbols)) = index.symbols(file)
+            && !symbols.is_empty()
+        {
+            total_symbols += symbols.len();
+            files_with_symbols += 1;
+        }
+    }
+
+    result.push_str("📂 File Types:\n");
+    for (ext, count) in extensions.iter() {
+        result.push_str(&format!("  • .{}: {} files\n", ext, count));
+    }
+
+    result.push_str(&format!(
+        "\n📊 Statistics:\n\
+         • {} total files\n\
+         • {} files with symbols\n\
+         • {} total symbols\n\
+         • {}\n\
+         {}\
+         • Root: {}\n",
+        files.len(),
+        files_with_symbols,
+        total_symbols,
+        format_index_stats(index),
+        format_analysis_notes(notes),
+        root_dir.display()
+    ));
+
+    result
+}
+
+/// Linha do resumo com o tempo de indexação, para medir o ganho da cache e do paralelismo
+fn format_index_stats(index: &SymbolIndex) -> String {
+    let stats = index.stats();
+    format!(
+        "Indexed in {} ms: {} of {} files parsed on {} threads",
+        stats.elapsed.as_millis(),
+        stats.parsed,
+        stats.files,
+        stats.threads
+    )
+}
+
+/// Ficheiros mostrados sem símbolos e o motivo, e se a análise foi interrompida
+#[derive(Debug, Default)]
+struct AnalysisNotes {
+    /// Motivo, por ficheiro, pelo qual não foi analisado (ex.: demasiado grande)
+    skipped: HashMap<PathBuf, String>,
+    /// Explicação quando o limite de tempo interrompeu a análise
+    truncated: Option<String>,
+    /// Número de candidatos quando só os mais relevantes couberam em `max_files`
+    ranked_from: Option<usize>,
+    /// O que foi omitido para a árvore caber em `max_tokens`
+    elided: Option<String>,
+}
+
+/// Aviso no topo da saída quando a árvore é parcial
+fn format_truncation_marker(notes: &AnalysisNotes) -> String {
+    let mut marker = String::new();
+    if let Some(reason) = &notes.truncated {
+        marker.push_str(&format!("⏱️  Truncated due to time budget: {}\n\n", reason));
+    }
+    if let Some(elided) = &notes.elided {
+        marker.push_str(&format!("✂️  Elided to fit max_tokens: {}\n\n", elided));
+    }
+    marker
+}
+
+/// Linhas do resumo sobre ficheiros ignorados e análise interrompida
+fn format_analysis_notes(notes: &AnalysisNotes) -> String {
+    let mut lines = String::new();
+    if !notes.skipped.is_empty() {
+        lines.push_str(&format!(
+            "• {} files shown without symbols (⏭️)\n",
+            notes.skipped.len()
+        ));
+    }
+    if let Some(candidates) = notes.ranked_from {
+        lines.push_str(&format!(
+            "• Most relevant files of {} candidates (max_files)\n",
+            candidates
+        ));
+    }
+    if notes.truncated.is_some() {
+        lines.push_str("• Truncated due to time budget\n");
+    }
+    if notes.elided.is_some() {
+        lines.push_str("• Elided to fit max_tokens (✂️)\n");
+    }
+    lines
+}
+
+/// Tamanho legível (`512 B`, `3.2 KB`, `1.5 MB`)
+fn format_bytes(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let size = bytes as f64;
+    if size >= KB * KB {
+        format!("{:.1} MB", size / (KB * KB))
+    } else if size >= KB {
+        format!("{:.1} KB", size / KB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Estrutura para representar um nó na árvore
+#[derive(Debug)]
+struct TreeNode {
+    file_path: Option<PathBuf>,
+    children: BTreeMap<std::ffi::OsString, TreeNode>,
+}
+
+impl TreeNode {
+    fn new() -> Self {
+        TreeNode {
+            file_path: None,
+            children: BTreeMap::new(),
+        }
+    }
+}
+
+/// Insere um ficheiro na estrutura da árvore
+fn insert_into_tree(
+    tree: &mut BTreeMap<std::ffi::OsString, TreeNode>,
+    components: &[&std::ffi::OsStr],
+    full_path: &Path,
 ) {
     if components.is_empty() {
         return;
@@ -316,11 +1058,14 @@ fn format_tree_node_with_limits(
     tree: &BTreeMap<std::ffi::OsString, TreeNode>,
     result: &mut String,
     prefix: &str,
-    is_root: bool,
-    max_symbols_per_file: usize,
+    depth: usize,
+    options: &TreeOptions,
+    index: &SymbolIndex,
+    notes: &AnalysisNotes,
 ) {
     const MAX_DIRS_TO_SHOW: usize = 50; // Limite de diretórios a mostrar
 
+    let is_root = depth == 1;
     let entries: Vec<_> = tree.iter().take(MAX_DIRS_TO_SHOW).collect();
 
     for (i, (name, node)) in entries.iter().enumerate() {
@@ -339,8 +1084,12 @@ fn format_tree_node_with_limits(
             // É um ficheiro - mostrar símbolos limitados
             result.push_str(&format!("{}{}{}\n", prefix, current_prefix, name_str));
 
-            // Extrair e mostrar símbolos (limitados)
-            match extract_symbols_from_file(file_path) {
+            // Mostrar símbolos (limitados) ou o motivo pelo qual o ficheiro não foi analisado
+            let symbols = match notes.skipped.get(file_path) {
+                Some(reason) => Err(reason.as_str()),
+                None => index.symbols(file_path).unwrap_or(Err("not indexed")),
+            };
+            match symbols {
                 Ok(symbols) => {
                     let symbols_prefix = if is_root {
                         ""
@@ -351,23 +1100,55 @@ fn format_tree_node_with_limits(
                     };
 
                     if !symbols.is_empty() {
-                        let symbols_to_show = symbols.iter().take(max_symbols_per_file);
+                        let max_symbols_per_file = options.max_symbols_per_file;
+                        let selected =
+                            most_important_symbols(file_path, symbols, max_symbols_per_file);
+                        let nested = nest_symbols(selected.as_deref().unwrap_or(symbols));
+                        let content = options
+                            .doc_summaries
+                            .then(|| index.document_text(file_path))
+                            .flatten();
+                        let lines: Vec<&str> = content
+                            .as_deref()
+                            .map(|content| content.lines().collect())
+                            .unwrap_or_default();
+                        let symbols_to_show: Vec<_> =
+                            nested.iter().take(max_symbols_per_file).collect();
                         let total_symbols = symbols.len();
 
-                        for (j, symbol) in symbols_to_show.enumerate() {
-                            let symbol_marker = if j == max_symbols_per_file - 1
-                                || (j == total_symbols - 1 && node.children.is_empty())
-                            {
-                                "└─ "
+                        let is_truncated = total_symbols > max_symbols_per_file;
+                        let mut parent_is_last = false;
+
+                        for (j, (depth, symbol)) in symbols_to_show.iter().enumerate() {
+                            // Último símbolo de topo: não há outro de profundidade 0 a seguir
+                            let is_last_top_level = !is_truncated
+                                && symbols_to_show[j + 1..].iter().all(|(d, _)| *d > 0);
+                            let is_last_shown =
+                                j == max_symbols_per_file - 1 || (*depth == 0 && is_last_top_level);
+                            // Membros de uma classe aparecem indentados sob ela
+                            let (indent, symbol_marker) = if *depth > 0 {
+                                let is_last_member = symbols_to_show
+                                    .get(j + 1)
+                                    .is_none_or(|(next_depth, _)| *next_depth == 0);
+                                (
+                                    if parent_is_last { "   " } else { "│  " },
+                                    if is_last_member { "└─ " } else { "├─ " },
+                                )
                             } else {
-                                "├─ "
+                                parent_is_last = is_last_shown;
+                                ("", if is_last_shown { "└─ " } else { "├─ " })
                             };
+                            let summary = relevant_context::doc_summary(&lines, symbol)
+                                .map(|summary| format!(" — {}", summary))
+                                .unwrap_or_default();
                             result.push_str(&format!(
-                                "{}{}  {}{}\n",
+                                "{}{}  {}{}{}{}\n",
                                 prefix,
                                 symbols_prefix,
+                                indent,
                                 symbol_marker,
-                                format_symbol(&symbol)
+                                format_symbol(&symbol.to_string()),
+                                summary
                             ));
                         }
 
@@ -382,7 +1163,7 @@ fn format_tree_node_with_limits(
                         }
                     }
                 }
-                Err(_) => {
+                Err(error) => {
                     let error_prefix = if is_root {
                         ""
                     } else if is_last {
@@ -390,12 +1171,28 @@ fn format_tree_node_with_limits(
                     } else {
                         "│   "
                     };
-                    result.push_str(&format!(
-                        "{}{}  └─ ⚠️  (parsing error)\n",
-                        prefix, error_prefix
-                    ));
+                    if notes.skipped.contains_key(file_path) {
+                        result
+                            .push_str(&format!("{}{}  └─ ⏭️  ({})\n", prefix, error_prefix, error));
+                    } else {
+                        result.push_str(&format!(
+                            "{}{}  └─ ⚠️  (parsing error)\n",
+                            prefix, error_prefix
+                        ));
+                    }
                 }
             }
+        } else if options
+            .collapse_depth
+            .is_some_and(|collapse_depth| depth >= collapse_depth)
+        {
+            // Diretoria reduzida a contagens para caber em `max_tokens`
+            let (files, symbols) = count_tree(&node.children, index);
+            result.push_str(&format!(
+                "{}{}📁 {}/ ({} files, {} symbols)\n",
+                prefix, current_prefix, name_str, files, symbols
+            ));
+            continue;
         } else {
             // É uma diretoria
             result.push_str(&format!("{}{}📁 {}/\n", prefix, current_prefix, name_str));
@@ -414,8 +1211,10 @@ fn format_tree_node_with_limits(
                 &node.children,
                 result,
                 child_prefix,
-                false,
-                max_symbols_per_file,
+                depth + 1,
+                options,
+                index,
+                notes,
             );
         }
     }
@@ -430,259 +1229,1146 @@ fn format_tree_node_with_limits(
     }
 }
 
-/// Formata um símbolo com ícones apropriados
-fn format_symbol(symbol: &str) -> String {
-    if symbol.starts_with("fn ") || symbol.contains("function") {
-        format!("🔧 {}", symbol)
-    } else if symbol.starts_with("struct ") || symbol.starts_with("class ") {
-        format!("🏗️  {}", symbol)
-    } else if symbol.starts_with("enum ") {
-        format!("🔢 {}", symbol)
-    } else if symbol.starts_with("trait ") || symbol.starts_with("interface ") {
-        format!("🎭 {}", symbol)
-    } else if symbol.starts_with("impl ") {
-        format!("⚙️  {}", symbol)
-    } else if symbol.starts_with("mod ") || symbol.starts_with("module ") {
-        format!("📦 {}", symbol)
-    } else if symbol.starts_with("const ") || symbol.starts_with("static ") {
-        format!("📌 {}", symbol)
-    } else if symbol.starts_with("let ") || symbol.starts_with("var ") {
-        format!("📊 {}", symbol)
-    } else {
-        format!("🔍 {}", symbol)
+/// Número de ficheiros e de símbolos dentro de uma diretoria
+fn count_tree(
+    tree: &BTreeMap<std::ffi::OsString, TreeNode>,
+    index: &SymbolIndex,
+) -> (usize, usize) {
+    tree.values().fold((0, 0), |(files, symbols), node| {
+        let (child_files, child_symbols) = count_tree(&node.children, index);
+        let own_symbols = match node.file_path.as_ref().and_then(|file| index.symbols(file)) {
+            Some(Ok(file_symbols)) => file_symbols.len(),
+            _ => 0,
+        };
+        (
+            files + child_files + usize::from(node.file_path.is_some()),
+            symbols + child_symbols + own_symbols,
+        )
+    })
+}
+
+/// Os `limit` símbolos mais importantes do ficheiro, pela ordem original, ou `None`
+/// se todos cabem ou o formato mantém a ordem do documento (ficam os primeiros)
+fn most_important_symbols(
+    file_path: &Path,
+    symbols: &[Symbol],
+    limit: usize,
+) -> Option<Vec<Symbol>> {
+    if symbols.len() <= limit || keeps_document_order(file_path) {
+        return None;
+    }
+    let mut positions: Vec<usize> = (0..symbols.len()).collect();
+    positions.sort_by_key(|position| (symbol_rank(&symbols[*position]), *position));
+    positions.truncate(limit);
+    positions.sort_unstable();
+    Some(
+        positions
+            .into_iter()
+            .map(|position| symbols[position].clone())
+            .collect(),
+    )
+}
+
+/// Importância de um símbolo quando nem todos cabem (menor é mais importante): tipos e
+/// módulos, depois funções, depois constantes e por fim o resto. Membros de uma classe
+/// vêm depois dos símbolos de topo do mesmo tipo.
+fn symbol_rank(symbol: &Symbol) -> usize {
+    let kind_rank = match symbol.kind.as_str() {
+        "struct" | "class" | "enum" | "union" | "record" | "trait" | "interface" | "protocol"
+        | "impl" | "extension" | "mod" | "module" | "namespace" | "component" => 0,
+        "fn" | "function" | "method" | "hook" | "macro" | "type" | "export" => 1,
+        "const" | "static" | "table" | "view" | "service" | "target" | "job" => 2,
+        _ => 3,
+    };
+    kind_rank + usize::from(symbol.container.is_some())
+}
+
+/// Ordena os símbolos para apresentação, colocando os membros de cada símbolo de topo
+/// (classes, chaves de configuração, ...) logo a seguir a ele, com profundidade 1
+fn nest_symbols(symbols: &[Symbol]) -> Vec<(usize, &Symbol)> {
+    let parent_names: Vec<&str> = symbols
+        .iter()
+        .filter(|symbol| symbol.container.is_none())
+        .map(|symbol| symbol.name.as_str())
+        .collect();
+    let is_member = |symbol: &Symbol| {
+        symbol
+            .container
+            .as_deref()
+            .is_some_and(|container| parent_names.contains(&container))
+    };
+
+    let mut nested = Vec::with_capacity(symbols.len());
+    for symbol in symbols.iter().filter(|symbol| !is_member(symbol)) {
+        nested.push((0, symbol));
+        if symbol.container.is_none() {
+            nested.extend(
+                symbols
+                    .iter()
+                    .filter(|member| member.container.as_deref() == Some(symbol.name.as_str()))
+                    .map(|member| (1, member)),
+            );
+        }
+    }
+    nested
+}
+
+/// Formata um símbolo com ícones apropriados
+fn format_symbol(symbol: &str) -> String {
+    if symbol.starts_with("fn ") || symbol.contains("function") {
+        format!("🔧 {}", symbol)
+    } else if symbol.starts_with("h1 ") || symbol.starts_with("h2 ") || symbol.starts_with("h3 ") {
+        format!("📖 {}", symbol)
+    } else if symbol.starts_with("key ") || symbol.starts_with("table ") {
+        format!("🔑 {}", symbol)
+    } else if symbol.starts_with("column ") {
+        format!("▫️  {}", symbol)
+    } else if symbol.starts_with("index ") {
+        format!("🔎 {}", symbol)
+    } else if symbol.starts_with("view ") {
+        format!("👁️  {}", symbol)
+    } else if symbol.starts_with("job ")
+        || symbol.starts_with("script ")
+        || symbol.starts_with("entrypoint ")
+        || symbol.starts_with("cmd ")
+    {
+        format!("▶️  {}", symbol)
+    } else if symbol.starts_with("target ") {
+        format!("🎯 {}", symbol)
+    } else if symbol.starts_with("port ") {
+        format!("🔌 {}", symbol)
+    } else if symbol.starts_with("service ") || symbol.starts_with("stage ") {
+        format!("🐳 {}", symbol)
+    } else if symbol.starts_with("dependency ") {
+        format!("🔗 {}", symbol)
+    } else if symbol.starts_with("export ") {
+        format!("📤 {}", symbol)
+    } else if symbol.starts_with("component ") {
+        format!("🧩 {}", symbol)
+    } else if symbol.starts_with("test ") {
+        format!("🧪 {}", symbol)
+    } else if symbol.starts_with("macro ") {
+        format!("🪄 {}", symbol)
+    } else if symbol.starts_with("extern ") {
+        format!("🌐 {}", symbol)
+    } else if symbol.starts_with("type ") {
+        format!("🏷️  {}", symbol)
+    } else if symbol.starts_with("hook ") {
+        format!("🪝 {}", symbol)
+    } else if symbol.starts_with("prop ") {
+        format!("🔹 {}", symbol)
+    } else if symbol.starts_with("emit ") {
+        format!("📣 {}", symbol)
+    } else if symbol.starts_with("struct ")
+        || symbol.starts_with("union ")
+        || symbol.starts_with("class ")
+        || symbol.starts_with("record ")
+    {
+        format!("🏗️  {}", symbol)
+    } else if symbol.starts_with("enum ") {
+        format!("🔢 {}", symbol)
+    } else if symbol.starts_with("trait ")
+        || symbol.starts_with("interface ")
+        || symbol.starts_with("protocol ")
+    {
+        format!("🎭 {}", symbol)
+    } else if symbol.starts_with("impl ") || symbol.starts_with("extension ") {
+        format!("⚙️  {}", symbol)
+    } else if symbol.starts_with("mod ")
+        || symbol.starts_with("module ")
+        || symbol.starts_with("namespace ")
+    {
+        format!("📦 {}", symbol)
+    } else if symbol.starts_with("const ") || symbol.starts_with("static ") {
+        format!("📌 {}", symbol)
+    } else if symbol.starts_with("let ") || symbol.starts_with("var ") {
+        format!("📊 {}", symbol)
+    } else {
+        format!("🔍 {}", symbol)
+    }
+}
+
+/// Tamanho máximo, por omissão, dos ficheiros analisados
+const DEFAULT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Tamanho aproximado, por omissão, da resposta de `get_relevant_context`
+const DEFAULT_CONTEXT_TOKENS: u64 = 4000;
+
+/// Limites da seleção de ficheiros onde `get_relevant_context` e `semantic_search` procuram
+const SEARCH_MAX_FILES: usize = 2000;
+const SEARCH_MAX_DEPTH: usize = 8;
+
+/// Diretorias ignoradas ao percorrer o workspace
+const IGNORED_DIRS: [&str; 17] = [
+    ".git",
+    "target",
+    "node_modules",
+    ".next",
+    "dist",
+    "build",
+    "coverage",
+    ".nyc_output",
+    "vendor",
+    "__pycache__",
+    ".pytest_cache",
+    ".vscode",
+    ".idea",
+    "tmp",
+    "temp",
+    ".cache",
+    ".DS_Store",
+];
+
+/// Extensões principais de código, mostradas antes das restantes
+const PRIORITY_EXTENSIONS: [&str; 8] = ["rs", "js", "ts", "tsx", "jsx", "py", "go", "java"];
+
+/// Extensões secundárias, incluídas até ao limite de ficheiros
+const SECONDARY_EXTENSIONS: [&str; 24] = [
+    "c",
+    "cpp",
+    "h",
+    "hpp",
+    "cs",
+    "php",
+    "rb",
+    "kt",
+    "swift",
+    "scala",
+    "sh",
+    "bash",
+    "zsh",
+    "sql",
+    "vue",
+    "svelte",
+    "md",
+    "yaml",
+    "yml",
+    "json",
+    "toml",
+    "xml",
+    "makefile",
+    "dockerfile",
+];
+
+/// Ficheiros específicos a ignorar (padrões)
+const IGNORED_FILE_PATTERNS: [&str; 13] = [
+    ".lock",
+    ".log",
+    ".tmp",
+    ".cache",
+    ".DS_Store",
+    "thumbs.db",
+    ".min.js",
+    ".min.css",
+    ".bundle.js",
+    ".bundle.css",
+    "package-lock.json",
+    "yarn.lock",
+    "Cargo.lock",
+];
+
+/// Prioridade de um ficheiro na seleção dos ficheiros do projeto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FilePriority {
+    Primary,
+    Secondary,
+}
+
+/// Verifica se uma diretoria é ignorada ao percorrer o workspace
+fn is_ignored_dir_name(name: &str) -> bool {
+    IGNORED_DIRS.contains(&name)
+}
+
+/// Prioridade do ficheiro no projeto, ou `None` se não é relevante
+fn project_file_priority(path: &Path, config: &LanguageConfig) -> Option<FilePriority> {
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+
+    // Verificar se tem extensão válida
+    let Some(extension) = path.extension() else {
+        // Incluir ficheiros sem extensão mas com nomes específicos
+        return (build_files::is_build_file(path) || file_name == "rakefile")
+            .then_some(FilePriority::Secondary);
+    };
+    let ext = extension.to_string_lossy().to_lowercase();
+
+    // Verificar se não é um ficheiro a ser ignorado
+    if IGNORED_FILE_PATTERNS
+        .iter()
+        .any(|&pattern| file_name.contains(pattern))
+    {
+        return None;
+    }
+
+    if PRIORITY_EXTENSIONS.contains(&ext.as_str()) {
+        Some(FilePriority::Primary)
+    } else if SECONDARY_EXTENSIONS.contains(&ext.as_str())
+        || build_files::is_build_file(path)
+        || config.grammar_for_path(path).is_some()
+    {
+        Some(FilePriority::Secondary)
+    } else {
+        None
+    }
+}
+
+/// Ficheiros escolhidos para a análise do workspace
+#[derive(Debug, Clone)]
+struct FileSelection {
+    files: Vec<PathBuf>,
+    /// Ficheiros relevantes encontrados; mais do que `files` quando `max_files` obrigou
+    /// a escolher os mais relevantes
+    candidates: usize,
+    /// Se a procura parou por ter sido atingido o `deadline`
+    timed_out: bool,
+}
+
+/// Coleta ficheiros de código fonte do projeto, ignorando diretorias e ficheiros irrelevantes.
+/// Com mais candidatos do que `max_files`, ficam os mais relevantes (ver `file_ranking`).
+fn collect_project_files_with_limits(
+    path: &Path,
+    max_files: usize,
+    max_depth: usize,
+    config: &LanguageConfig,
+    deadline: Option<Instant>,
+) -> FileSelection {
+    let mut candidates = Vec::new();
+    let mut timed_out = false;
+
+    for entry in WalkDir::new(path)
+        .max_depth(max_depth) // Usar profundidade configurável
+        .into_iter()
+        .filter_entry(|e| {
+            // Filtrar diretorias ignoradas
+            !e.file_type().is_dir() || !is_ignored_dir_name(&e.file_name().to_string_lossy())
+        })
+        .flatten()
+    {
+        let path = entry.path();
+
+        // Parar se o tempo disponível acabou
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            break;
+        }
+
+        // Apenas processar ficheiros (não diretorias)
+        if path.is_file()
+            && let Some(priority) = project_file_priority(path, config)
+        {
+            candidates.push((path.to_path_buf(), priority));
+        }
+    }
+
+    let total = candidates.len();
+    if total > max_files {
+        candidates = file_ranking::most_relevant(path, candidates, max_files, deadline);
+    }
+
+    // Combinar arquivos priorizando os principais
+    let (mut priority_files, mut secondary_files): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|(_, priority)| *priority == FilePriority::Primary);
+    priority_files.sort();
+    secondary_files.sort();
+
+    FileSelection {
+        files: priority_files
+            .into_iter()
+            .chain(secondary_files)
+            .map(|(file, _)| file)
+            .collect(),
+        candidates: total,
+        timed_out,
+    }
+}
+
+/// Símbolo de código extraído por tree-sitter, com a localização da sua definição
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Symbol {
+    /// Tipo do símbolo (`fn`, `struct`, `class`, ...), derivado do nome da captura
+    kind: String,
+    name: String,
+    /// Linhas (base 1) onde começa e termina a definição
+    start_line: usize,
+    end_line: usize,
+    /// Intervalo de bytes da definição completa no conteúdo original
+    start_byte: usize,
+    end_byte: usize,
+    /// Informação adicional mostrada após o nome (ex.: tipo das props de um componente)
+    detail: Option<String>,
+    /// Classe onde o símbolo está definido, para aninhar métodos e atributos
+    container: Option<String>,
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.kind.is_empty() {
+            write!(f, "{}", self.name)?;
+        } else {
+            write!(f, "{} {}", self.kind, self.name)?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ordena os símbolos pelo nome e remove duplicados, exceto nos formatos que
+/// mantêm a ordem do documento
+fn sort_symbols(file_path: &Path, mut symbols: Vec<Symbol>) -> Vec<Symbol> {
+    if keeps_document_order(file_path) {
+        return symbols;
+    }
+
+    // Remover duplicados e ordenar
+    symbols.sort_by(|a, b| {
+        a.to_string()
+            .cmp(&b.to_string())
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    symbols.dedup_by(|a, b| a.container == b.container && a.to_string() == b.to_string());
+
+    symbols
+}
+
+/// Outlines de Markdown, de configuração, de SQL, de build e de componentes
+/// mantêm a ordem do documento em vez de serem ordenados pelo nome
+fn keeps_document_order(file_path: &Path) -> bool {
+    markdown::is_markdown_path(file_path)
+        || config_outline::is_config_path(file_path)
+        || sql_schema::is_sql_path(file_path)
+        || build_files::is_build_file(file_path)
+        || sfc::is_sfc_path(file_path)
+}
+
+/// Extrai símbolos de um conteúdo arbitrário, usando o caminho apenas para escolher a linguagem.
+/// Devolve todas as ocorrências pela ordem em que aparecem, sem remover duplicados.
+fn extract_symbols_from_source(
+    file_path: &Path,
+    content: &str,
+    config: &LanguageConfig,
+) -> Result<Vec<Symbol>, anyhow::Error> {
+    extract_symbols_with_tree(file_path, content, config, None, None).map(|(symbols, _)| symbols)
+}
+
+/// Como `extract_symbols_from_source`, reutilizando `old_tree` (já ajustada com `Tree::edit`)
+/// para um parse incremental. Devolve também a árvore nova, se o ficheiro usa tree-sitter.
+/// Um parse que não termina até ao `deadline` falha com `ParseTimedOut`.
+fn extract_symbols_with_tree(
+    file_path: &Path,
+    content: &str,
+    config: &LanguageConfig,
+    old_tree: Option<&Tree>,
+    deadline: Option<Instant>,
+) -> Result<(Vec<Symbol>, Option<Tree>), anyhow::Error> {
+    // Markdown não usa tree-sitter: o outline vem dos cabeçalhos
+    if markdown::is_markdown_path(file_path) {
+        return Ok((markdown::extract_markdown_symbols(content), None));
+    }
+
+    // TOML/YAML/JSON: outline superficial das chaves
+    if config_outline::is_config_path(file_path) {
+        return Ok((
+            config_outline::extract_config_symbols(file_path, content),
+            None,
+        ));
+    }
+
+    // SQL: tabelas, colunas, índices, vistas e funções
+    if sql_schema::is_sql_path(file_path) {
+        return Ok((sql_schema::extract_sql_symbols(content), None));
+    }
+
+    // Makefile e Dockerfile: alvos, fases, portas e comandos de arranque
+    if build_files::is_build_file(file_path) {
+        return Ok((build_files::extract_build_symbols(file_path, content), None));
+    }
+
+    // Vue/Svelte: extração JS/TS sobre os blocos `<script>`
+    if sfc::is_sfc_path(file_path) {
+        return Ok((sfc::extract_sfc_symbols(file_path, content, config)?, None));
+    }
+
+    // Determinar a linguagem e a query pela extensão
+    // Gramáticas embutidas primeiro, depois as carregadas do config.json.
+    // As queries embutidas não mudam, por isso a sua impressão digital é 0.
+    let (language, language_name, builtin_query, builtin_fingerprint) =
+        match language_for_path(file_path)
+            .map(|(language, name, query)| (language, name, query, 0))
+            .or_else(|| {
+                config.grammar_for_path(file_path).map(|grammar| {
+                    (
+                        grammar.language,
+                        grammar.name.as_str(),
+                        grammar.query.as_str(),
+                        grammar.fingerprint,
+                    )
+                })
+            }) {
+            Some(language_and_query) => language_and_query,
+            None => return Ok((vec![], None)), // Linguagem não suportada, retornar lista vazia
+        };
+
+    // Parse do código com o parser da thread atual, incremental se houver árvore anterior
+    let tree = parse_source(language, content, old_tree, deadline)?
+        .ok_or_else(|| anyhow::anyhow!("Falha ao fazer parse do ficheiro"))?;
+
+    // Obter a query compilada (embutida e/ou definida pelo utilizador)
+    let compiled = cached_query(
+        language,
+        language_name,
+        builtin_query,
+        builtin_fingerprint,
+        config.custom_query(language_name),
+    )?;
+    let (query, custom_start) = (&compiled.0, compiled.1);
+    let mut cursor = QueryCursor::new();
+    let matches = cursor.matches(query, tree.root_node(), content.as_bytes());
+
+    let mut symbols = Vec::new();
+    let mut custom_symbols: Vec<Symbol> = Vec::new();
+    let is_javascript_family = matches!(
+        file_path.extension().and_then(|ext| ext.to_str()),
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") | Some("ts") | Some("tsx")
+    );
+    let is_python = matches!(
+        file_path.extension().and_then(|ext| ext.to_str()),
+        Some("py") | Some("pyi")
+    );
+    let is_rust = file_path.extension().and_then(|ext| ext.to_str()) == Some("rs");
+
+    for mat in matches {
+        // Padrões das queries do utilizador vêm depois da query embutida
+        let is_custom = query.start_byte_for_pattern(mat.pattern_index) >= custom_start;
+
+        // Capturas `*.receiver` qualificam o nome do símbolo (ex.: métodos Go `User.Save`)
+        let receiver = mat
+            .captures
+            .iter()
+            .find(|capture| query.capture_names()[capture.index as usize].ends_with(".receiver"))
+            .and_then(|capture| capture.node.utf8_text(content.as_bytes()).ok());
+
+        for capture in mat.captures {
+            let node = capture.node;
+            let capture_name = &query.capture_names()[capture.index as usize];
+            // Capturas auxiliares (`@_nome`) servem apenas para predicados como `#eq?`
+            if capture_name.ends_with(".receiver") || capture_name.starts_with('_') {
+                continue;
+            }
+
+            if let Ok(symbol_name) = node.utf8_text(content.as_bytes()) {
+                let mut definition = definition_node(node);
+                let mut kind = symbol_kind_for_capture(capture_name).to_string();
+                // Nas queries do utilizador, `@rota.name` dá origem ao tipo `rota`
+                if is_custom && kind.is_empty() {
+                    kind = capture_name
+                        .split('.')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+                }
+                let mut detail = None;
+                let mut container = None;
+
+                // Python: membros aninhados na classe, decorators/async e constantes
+                if is_python {
+                    container = enclosing_python_class(definition, content);
+                    if !is_custom && kind == "var" && is_constant_name(symbol_name) {
+                        kind = "const".to_string();
+                    }
+                    detail = python_modifiers(definition, content);
+                    if let Some(decorated) = definition
+                        .parent()
+                        .filter(|parent| parent.kind() == "decorated_definition")
+                    {
+                        definition = decorated;
+                    }
+                }
+
+                // Rust: testes, funções `extern`, itens associados e membros de blocos `extern`
+                if is_rust && !is_custom {
+                    match kind.as_str() {
+                        "fn" if is_rust_test(definition, content) => kind = "test".to_string(),
+                        "fn" => detail = rust_extern_abi(definition, content),
+                        "const" | "type" => detail = rust_item_owner(definition, content),
+                        "extern" => definition = definition.parent().unwrap_or(definition),
+                        _ => {}
+                    }
+                    container = enclosing_rust_extern_block(definition, content);
+                }
+
+                // `export default App` e `module.exports = App`
+                if capture_name.ends_with(".default") && symbol_name != "default" {
+                    detail = Some("default".to_string());
+                }
+
+                // Variáveis JS/TS: funções atribuídas passam a `fn`, `require(...)` é descartado
+                if is_javascript_family && !is_custom && kind == "var" {
+                    match javascript_variable_kind(definition, content) {
+                        Some(variable_kind) => kind = variable_kind.to_string(),
+                        None => continue,
+                    }
+                }
+
+                // Componentes e hooks React têm um tipo próprio
+                if is_javascript_family
+                    && !is_custom
+                    && let Some((react_kind, props)) =
+                        classify_react_symbol(&kind, symbol_name, definition, content)
+                {
+                    kind = react_kind.to_string();
+                    detail = props.map(|props| format!("props: {}", props));
+                }
+
+                let symbol = Symbol {
+                    kind,
+                    name: match receiver {
+                        Some(receiver) => format!("{}.{}", receiver, symbol_name),
+                        None => symbol_name.to_string(),
+                    },
+                    start_line: definition.start_position().row + 1,
+                    end_line: definition.end_position().row + 1,
+                    start_byte: definition.start_byte(),
+                    end_byte: definition.end_byte(),
+                    detail,
+                    container,
+                };
+                if is_custom {
+                    custom_symbols.push(symbol);
+                } else {
+                    symbols.push(symbol);
+                }
+            }
+        }
+    }
+
+    // Uma captura do utilizador substitui a embutida para a mesma definição
+    symbols.retain(|symbol| {
+        !custom_symbols
+            .iter()
+            .any(|custom| custom.start_byte == symbol.start_byte && custom.name == symbol.name)
+    });
+    symbols.extend(custom_symbols);
+
+    symbols.sort_by_key(|symbol| symbol.start_byte);
+
+    Ok((symbols, Some(tree)))
+}
+
+/// Parse interrompido por ter sido atingido o `deadline`
+#[derive(Debug)]
+pub(crate) struct ParseTimedOut;
+
+impl std::fmt::Display for ParseTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "parse timed out")
+    }
+}
+
+impl std::error::Error for ParseTimedOut {}
+
+/// Faz parse com o parser da thread atual, reutilizado entre ficheiros. Com `old_tree`
+/// o parse é incremental e só as partes editadas são analisadas de novo. Com
+/// `deadline` o tree-sitter desiste do parse quando o tempo acaba.
+fn parse_source(
+    language: Language,
+    content: &str,
+    old_tree: Option<&Tree>,
+    deadline: Option<Instant>,
+) -> Result<Option<Tree>, anyhow::Error> {
+    thread_local! {
+        static PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+    }
+
+    // 0 desativa o limite; com o tempo esgotado fica 1 µs, porque 0 não limitaria
+    let timeout_micros = deadline.map_or(0, |deadline| {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        (remaining.as_micros() as u64).max(1)
+    });
+
+    PARSER.with(|parser| {
+        let mut parser = parser.borrow_mut();
+        parser.set_language(language)?;
+        parser.set_timeout_micros(timeout_micros);
+        match parser.parse(content, old_tree) {
+            Some(tree) => Ok(Some(tree)),
+            // Sem `reset`, o próximo parse continuaria o que foi interrompido
+            None if deadline.is_some() => {
+                parser.reset();
+                Err(ParseTimedOut.into())
+            }
+            None => Ok(None),
+        }
+    })
+}
+
+/// Query compilada, partilhada entre threads e compilada uma única vez por linguagem
+/// e pelas impressões digitais das queries, calculadas ao carregar a configuração
+fn cached_query(
+    language: Language,
+    language_name: &str,
+    builtin_query: &str,
+    builtin_fingerprint: u64,
+    custom_query: Option<&CustomQuery>,
+) -> Result<Arc<(Query, usize)>, anyhow::Error> {
+    type QueryCache = Mutex<HashMap<(String, u64, u64), Arc<(Query, usize)>>>;
+    static QUERIES: OnceLock<QueryCache> = OnceLock::new();
+
+    let key = (
+        language_name.to_string(),
+        builtin_fingerprint,
+        custom_query.map_or(0, |custom_query| custom_query.fingerprint),
+    );
+
+    let mut queries = workspace_watcher::lock(QUERIES.get_or_init(Default::default));
+    if let Some(query) = queries.get(&key) {
+        return Ok(query.clone());
+    }
+    let query = Arc::new(compile_query(
+        language,
+        language_name,
+        builtin_query,
+        custom_query,
+    )?);
+    queries.insert(key, query.clone());
+    Ok(query)
+}
+
+/// Compila a query da linguagem. A query do utilizador é acrescentada à embutida
+/// (ou substitui-a com `; override`); devolve também o byte onde começam os padrões
+/// do utilizador. Uma query do utilizador inválida é ignorada com um aviso.
+fn compile_query(
+    language: Language,
+    language_name: &str,
+    builtin_query: &str,
+    custom_query: Option<&CustomQuery>,
+) -> Result<(Query, usize), anyhow::Error> {
+    let Some(custom_query) = custom_query else {
+        return Ok((Query::new(language, builtin_query)?, usize::MAX));
+    };
+
+    let (source, custom_start) = if custom_query.replaces_builtin {
+        (custom_query.source.clone(), 0)
+    } else {
+        (
+            format!("{}\n{}", builtin_query, custom_query.source),
+            builtin_query.len() + 1,
+        )
+    };
+
+    match Query::new(language, &source) {
+        Ok(query) => Ok((query, custom_start)),
+        Err(error) => {
+            eprintln!(
+                "⚠️  Invalid custom query for {}: {} (using the built-in query)",
+                language_name, error
+            );
+            Ok((Query::new(language, builtin_query)?, usize::MAX))
+        }
+    }
+}
+
+/// Verifica se a função Rust tem um atributo de teste
+/// (`#[test]`, `#[tokio::test]`, `#[rstest]`, `#[test_case(...)]`)
+fn is_rust_test(function: Node, content: &str) -> bool {
+    let mut sibling = function.prev_named_sibling();
+    while let Some(node) = sibling {
+        match node.kind() {
+            "attribute_item" => {
+                let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+                let path = text
+                    .trim_start_matches("#[")
+                    .trim_end_matches(']')
+                    .split('(')
+                    .next()
+                    .unwrap_or_default()
+                    .trim();
+                if matches!(
+                    path.rsplit("::").next(),
+                    Some("test") | Some("rstest") | Some("test_case")
+                ) {
+                    return true;
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = node.prev_named_sibling();
+    }
+    false
+}
+
+/// ABI de uma função `extern "C" fn` (`extern "C"`), se a tiver
+fn rust_extern_abi(function: Node, content: &str) -> Option<String> {
+    let mut cursor = function.walk();
+    let modifiers = function
+        .children(&mut cursor)
+        .find(|child| child.kind() == "function_modifiers")?;
+    let mut cursor = modifiers.walk();
+    let extern_modifier = modifiers
+        .children(&mut cursor)
+        .find(|child| child.kind() == "extern_modifier")?;
+    extern_modifier
+        .utf8_text(content.as_bytes())
+        .ok()
+        .map(|text| text.to_string())
+}
+
+/// Trait ou impl onde está definido um const/type associado (`impl Display for User`)
+fn rust_item_owner(definition: Node, content: &str) -> Option<String> {
+    let owner = definition
+        .parent()
+        .filter(|parent| parent.kind() == "declaration_list")?
+        .parent()?;
+    let field_text = |field: &str| {
+        owner
+            .child_by_field_name(field)
+            .and_then(|node| node.utf8_text(content.as_bytes()).ok())
+    };
+
+    match owner.kind() {
+        "trait_item" => Some(format!("trait {}", field_text("name")?)),
+        "impl_item" => Some(match field_text("trait") {
+            Some(trait_name) => format!("impl {} for {}", trait_name, field_text("type")?),
+            None => format!("impl {}", field_text("type")?),
+        }),
+        _ => None,
+    }
+}
+
+/// ABI do bloco `extern "C" { ... }` que contém a definição, para a aninhar no bloco
+fn enclosing_rust_extern_block(definition: Node, content: &str) -> Option<String> {
+    let mut node = definition.parent()?;
+    while node.kind() != "foreign_mod_item" {
+        node = node.parent()?;
     }
+    let mut cursor = node.walk();
+    let extern_modifier = node
+        .children(&mut cursor)
+        .find(|child| child.kind() == "extern_modifier")?;
+    let mut cursor = extern_modifier.walk();
+    let abi = extern_modifier
+        .children(&mut cursor)
+        .find(|child| child.kind() == "string_literal")?;
+    abi.utf8_text(content.as_bytes())
+        .ok()
+        .map(|text| text.to_string())
 }
 
-/// Coleta ficheiros de código fonte do projeto, ignorando diretorias e ficheiros irrelevantes
-fn collect_project_files_with_limits(
-    path: &Path,
-    max_files: usize,
-    max_depth: usize,
-) -> Vec<PathBuf> {
-    let mut files = Vec::new();
-
-    // Diretorias a ignorar
-    let ignored_dirs = [
-        ".git",
-        "target",
-        "node_modules",
-        ".next",
-        "dist",
-        "build",
-        "coverage",
-        ".nyc_output",
-        "vendor",
-        "__pycache__",
-        ".pytest_cache",
-        ".vscode",
-        ".idea",
-        "tmp",
-        "temp",
-        ".cache",
-        ".DS_Store",
-    ];
-
-    // Priorizar extensões principais de código
-    let priority_extensions = ["rs", "js", "ts", "tsx", "jsx", "py", "go", "java"];
-    let secondary_extensions = [
-        "c",
-        "cpp",
-        "h",
-        "hpp",
-        "cs",
-        "php",
-        "rb",
-        "kt",
-        "swift",
-        "scala",
-        "sh",
-        "bash",
-        "zsh",
-        "sql",
-        "vue",
-        "svelte",
-        "md",
-        "yaml",
-        "yml",
-        "json",
-        "toml",
-        "xml",
-        "makefile",
-        "dockerfile",
-    ];
+/// Classifica um `variable_declarator` JS/TS pelo valor atribuído: `fn` para arrow
+/// functions e function expressions, `var` para o resto, e `None` para imports
+/// (`require(...)`, `await import(...)`), que não são símbolos do ficheiro
+fn javascript_variable_kind(declarator: Node, content: &str) -> Option<&'static str> {
+    let Some(value) = declarator.child_by_field_name("value") else {
+        return Some("var");
+    };
 
-    // Ficheiros específicos a ignorar (padrões)
-    let ignored_file_patterns = [
-        ".lock",
-        ".log",
-        ".tmp",
-        ".cache",
-        ".DS_Store",
-        "thumbs.db",
-        ".min.js",
-        ".min.css",
-        ".bundle.js",
-        ".bundle.css",
-        "package-lock.json",
-        "yarn.lock",
-        "Cargo.lock",
-    ];
+    match value.kind() {
+        "arrow_function" | "function_expression" | "generator_function" => Some("fn"),
+        _ if is_import_expression(value, content) => None,
+        _ => Some("var"),
+    }
+}
 
-    let mut priority_files = Vec::new();
-    let mut secondary_files = Vec::new();
+/// Verifica se uma expressão é `require(...)`/`import(...)`, possivelmente dentro de
+/// `await` ou de um acesso a membro (`require("x").default`)
+fn is_import_expression(node: Node, content: &str) -> bool {
+    match node.kind() {
+        "call_expression" => node
+            .child_by_field_name("function")
+            .is_some_and(|function| {
+                function.kind() == "import"
+                    || (function.kind() == "identifier"
+                        && function.utf8_text(content.as_bytes()) == Ok("require"))
+            }),
+        "await_expression" | "member_expression" => node
+            .named_child(0)
+            .is_some_and(|inner| is_import_expression(inner, content)),
+        _ => false,
+    }
+}
 
-    for entry in WalkDir::new(path)
-        .max_depth(max_depth) // Usar profundidade configurável
-        .into_iter()
-        .filter_entry(|e| {
-            // Filtrar diretorias ignoradas
-            if e.file_type().is_dir() {
-                let dir_name = e.file_name().to_string_lossy();
-                !ignored_dirs.iter().any(|&ignored| dir_name == ignored)
+/// Classifica funções, variáveis e classes JS/TS como componentes ou hooks React.
+/// Retorna o novo tipo e, para componentes, o tipo das props quando anotado.
+fn classify_react_symbol(
+    kind: &str,
+    name: &str,
+    definition: Node,
+    content: &str,
+) -> Option<(&'static str, Option<String>)> {
+    let starts_uppercase = name.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+    let is_hook_name = name.starts_with("use")
+        && name[3..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+
+    match (kind, definition.kind()) {
+        ("fn", "function_declaration") => {
+            if is_hook_name {
+                Some(("hook", None))
+            } else if starts_uppercase && contains_jsx(definition) {
+                Some(("component", props_type_of_function(definition, content)))
             } else {
-                true
+                None
             }
-        })
-    {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-
-            // Parar se já temos muitos arquivos
-            if priority_files.len() + secondary_files.len() >= max_files {
-                break;
+        }
+        ("fn" | "var", "variable_declarator") => {
+            let value = definition.child_by_field_name("value")?;
+            // `memo(() => ...)`/`forwardRef(...)` envolvem a função num call_expression
+            let is_function_value = matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "call_expression"
+            );
+            if !is_function_value {
+                return None;
             }
 
-            // Apenas processar ficheiros (não diretorias)
-            if path.is_file() {
-                // Verificar se tem extensão válida
-                if let Some(extension) = path.extension() {
-                    let ext = extension.to_string_lossy().to_lowercase();
-
-                    // Verificar se não é um ficheiro a ser ignorado
-                    let file_name = path
-                        .file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_lowercase();
-
-                    let should_ignore = ignored_file_patterns
-                        .iter()
-                        .any(|&pattern| file_name.contains(pattern));
-
-                    if !should_ignore {
-                        if priority_extensions.contains(&ext.as_str()) {
-                            priority_files.push(path.to_path_buf());
-                        } else if secondary_extensions.contains(&ext.as_str()) {
-                            secondary_files.push(path.to_path_buf());
-                        }
-                    }
-                }
-                // Incluir ficheiros sem extensão mas com nomes específicos
-                else if let Some(file_name) = path.file_name() {
-                    let name = file_name.to_string_lossy().to_lowercase();
-                    if name == "makefile" || name == "dockerfile" || name == "rakefile" {
-                        secondary_files.push(path.to_path_buf());
-                    }
-                }
+            if is_hook_name && value.kind() != "call_expression" {
+                Some(("hook", None))
+            } else if starts_uppercase && contains_jsx(value) {
+                // `const Button: React.FC<ButtonProps> = ...` ou `({ ... }: ButtonProps) => ...`
+                let props = definition
+                    .child_by_field_name("type")
+                    .and_then(|annotation| first_type_argument(annotation, content))
+                    .or_else(|| props_type_of_function(value, content));
+                Some(("component", props))
+            } else {
+                None
+            }
+        }
+        ("class", "class_declaration" | "class") => {
+            let heritage = (0..definition.named_child_count())
+                .filter_map(|i| definition.named_child(i))
+                .find(|child| child.kind() == "class_heritage")?;
+            let heritage_text = heritage.utf8_text(content.as_bytes()).ok()?;
+            if heritage_text.contains("Component") {
+                Some(("component", first_type_argument(heritage, content)))
+            } else {
+                None
             }
         }
+        _ => None,
     }
-
-    // Combinar arquivos priorizando os principais
-    priority_files.sort();
-    secondary_files.sort();
-
-    files.extend(priority_files);
-
-    // Adicionar arquivos secundários até o limite
-    let remaining_capacity = max_files.saturating_sub(files.len());
-    files.extend(secondary_files.into_iter().take(remaining_capacity));
-
-    files
 }
 
-/// Extrai símbolos de código de um ficheiro usando tree-sitter
-fn extract_symbols_from_file(file_path: &Path) -> Result<Vec<String>, anyhow::Error> {
-    // Ler o conteúdo do ficheiro
-    let content = fs::read_to_string(file_path)?;
+/// Verifica se algum descendente do nó é JSX
+fn contains_jsx(node: Node) -> bool {
+    if node.kind().starts_with("jsx_") {
+        return true;
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(contains_jsx)
+}
 
-    // Determinar a linguagem pela extensão
-    let language = match file_path.extension().and_then(|ext| ext.to_str()) {
-        Some("rs") => Some(tree_sitter_rust::language()),
-        Some("js") | Some("jsx") => Some(tree_sitter_javascript::language()),
-        Some("ts") | Some("tsx") => Some(tree_sitter_typescript::language_typescript()),
-        Some("py") => Some(tree_sitter_python::language()),
-        _ => None,
+/// Tipo anotado no primeiro parâmetro de uma função (`({ label }: ButtonProps)`)
+fn props_type_of_function(function: Node, content: &str) -> Option<String> {
+    // Procurar a função dentro de wrappers como `memo(...)`
+    let function = if function.kind() == "call_expression" {
+        let arguments = function.child_by_field_name("arguments")?;
+        (0..arguments.named_child_count())
+            .filter_map(|i| arguments.named_child(i))
+            .find(|arg| matches!(arg.kind(), "arrow_function" | "function_expression"))?
+    } else {
+        function
     };
 
-    let language = match language {
-        Some(lang) => lang,
-        None => return Ok(vec![]), // Linguagem não suportada, retornar lista vazia
-    };
+    let parameters = function.child_by_field_name("parameters")?;
+    let first = parameters.named_child(0)?;
+    let annotation = first.child_by_field_name("type")?;
+    let text = annotation.utf8_text(content.as_bytes()).ok()?;
+    Some(text.trim_start_matches(':').trim().to_string())
+}
 
-    // Criar parser e definir a linguagem
-    let mut parser = Parser::new();
-    parser.set_language(language)?;
+/// Primeiro argumento de tipo encontrado num nó (`React.FC<Props>` -> `Props`)
+fn first_type_argument(node: Node, content: &str) -> Option<String> {
+    if node.kind() == "type_arguments" {
+        return node
+            .named_child(0)
+            .and_then(|arg| arg.utf8_text(content.as_bytes()).ok())
+            .map(|text| text.to_string());
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    children
+        .into_iter()
+        .find_map(|child| first_type_argument(child, content))
+}
 
-    // Parse do código
-    let tree = parser
-        .parse(&content, None)
-        .ok_or_else(|| anyhow::anyhow!("Falha ao fazer parse do ficheiro"))?;
+/// Nome da classe Python que contém diretamente a definição (métodos e atributos)
+fn enclosing_python_class(definition: Node, content: &str) -> Option<String> {
+    let mut node = definition.parent()?;
+    loop {
+        match node.kind() {
+            "class_definition" => {
+                return node
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(content.as_bytes()).ok())
+                    .map(|name| name.to_string());
+            }
+            // Funções aninhadas não pertencem à classe exterior
+            "function_definition" => return None,
+            _ => node = node.parent()?,
+        }
+    }
+}
 
-    // Definir queries para extrair símbolos baseado na linguagem
-    let query_source = match file_path.extension().and_then(|ext| ext.to_str()) {
-        Some("rs") => get_rust_query(),
-        Some("js") | Some("jsx") => get_javascript_query(),
-        Some("ts") | Some("tsx") => get_typescript_query(),
-        Some("py") => get_python_query(),
-        _ => return Ok(vec![]),
-    };
+/// Modificadores de uma definição Python: `async` e os decorators aplicados
+fn python_modifiers(definition: Node, content: &str) -> Option<String> {
+    let mut modifiers = Vec::new();
 
-    // Criar e executar a query
-    let query = Query::new(language, &query_source)?;
-    let mut cursor = QueryCursor::new();
-    let matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+    if definition.kind() == "function_definition"
+        && definition
+            .child(0)
+            .is_some_and(|first| first.kind() == "async")
+    {
+        modifiers.push("async".to_string());
+    }
 
-    let mut symbols = Vec::new();
+    if let Some(decorated) = definition
+        .parent()
+        .filter(|parent| parent.kind() == "decorated_definition")
+    {
+        let mut cursor = decorated.walk();
+        for decorator in decorated
+            .named_children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+        {
+            if let Ok(text) = decorator.utf8_text(content.as_bytes()) {
+                modifiers.push(text.trim().to_string());
+            }
+        }
+    }
 
-    for mat in matches {
-        for capture in mat.captures {
-            let node = capture.node;
-            let capture_name = &query.capture_names()[capture.index as usize];
+    if modifiers.is_empty() {
+        None
+    } else {
+        Some(modifiers.join(", "))
+    }
+}
 
-            if let Ok(symbol_name) = node.utf8_text(content.as_bytes()) {
-                // Adicionar prefixo baseado no tipo de símbolo
-                let formatted_symbol = match capture_name {
-                    name if name.contains("function") => format!("fn {}", symbol_name),
-                    name if name.contains("struct") => format!("struct {}", symbol_name),
-                    name if name.contains("class") => format!("class {}", symbol_name),
-                    name if name.contains("enum") => format!("enum {}", symbol_name),
-                    name if name.contains("trait") => format!("trait {}", symbol_name),
-                    name if name.contains("interface") => format!("interface {}", symbol_name),
-                    name if name.contains("type") => format!("type {}", symbol_name),
-                    name if name.contains("impl") => format!("impl {}", symbol_name),
-                    name if name.contains("mod") => format!("mod {}", symbol_name),
-                    name if name.contains("const") => format!("const {}", symbol_name),
-                    name if name.contains("static") => format!("static {}", symbol_name),
-                    name if name.contains("method") => format!("method {}", symbol_name),
-                    name if name.contains("variable") => format!("var {}", symbol_name),
-                    name if name.contains("import") => format!("import {}", symbol_name),
-                    _ => symbol_name.to_string(),
-                };
-                symbols.push(formatted_symbol);
-            }
+/// Nomes em `UPPER_CASE` são tratados como constantes
+fn is_constant_name(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Linguagem, nome da linguagem (usado para as queries do utilizador, ex.: `rust.scm`)
+/// e query embutida para o ficheiro, escolhidos pela extensão
+fn language_for_path(file_path: &Path) -> Option<(Language, &'static str, &'static str)> {
+    match file_path.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => Some((tree_sitter_rust::language(), "rust", get_rust_query())),
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => Some((
+            tree_sitter_javascript::language(),
+            "javascript",
+            get_javascript_query(),
+        )),
+        Some("ts") => Some((
+            tree_sitter_typescript::language_typescript(),
+            "typescript",
+            get_typescript_query(),
+        )),
+        // TSX precisa da gramática própria para reconhecer JSX
+        Some("tsx") => Some((
+            tree_sitter_typescript::language_tsx(),
+            "typescript",
+            get_typescript_query(),
+        )),
+        Some("py") | Some("pyi") => {
+            Some((tree_sitter_python::language(), "python", get_python_query()))
+        }
+        Some("go") => Some((tree_sitter_go::language(), "go", get_go_query())),
+        Some("java") => Some((tree_sitter_java::language(), "java", get_java_query())),
+        Some("c") => Some((tree_sitter_c::language(), "c", get_c_query())),
+        Some("cpp") | Some("cc") | Some("cxx") | Some("h") | Some("hpp") | Some("hh") => {
+            Some((tree_sitter_cpp::language(), "cpp", get_cpp_query()))
+        }
+        Some("cs") => Some((
+            tree_sitter_c_sharp::language(),
+            "csharp",
+            get_csharp_query(),
+        )),
+        Some("rb") | Some("rake") => Some((tree_sitter_ruby::language(), "ruby", get_ruby_query())),
+        Some("php") => Some((tree_sitter_php::language_php(), "php", get_php_query())),
+        Some("kt") | Some("kts") => {
+            Some((tree_sitter_kotlin::language(), "kotlin", get_kotlin_query()))
+        }
+        Some("swift") => Some((tree_sitter_swift::language(), "swift", get_swift_query())),
+        Some("sh") | Some("bash") | Some("zsh") => {
+            Some((tree_sitter_bash::language(), "bash", get_shell_query()))
         }
+        // Ficheiros sem extensão reconhecidos pelo nome
+        None if file_path
+            .file_name()
+            .is_some_and(|name| name.eq_ignore_ascii_case("rakefile")) =>
+        {
+            Some((tree_sitter_ruby::language(), "ruby", get_ruby_query()))
+        }
+        _ => None,
     }
+}
 
-    // Remover duplicados e ordenar
-    symbols.sort();
-    symbols.dedup();
+/// Encontra o nó da definição completa a partir do nome capturado.
+/// Em C/C++ o nome está dentro de declaradores (`function_declarator`, ...),
+/// por isso sobe-se até ao primeiro nó que não seja um declarador.
+fn definition_node(name_node: Node) -> Node {
+    let mut node = match name_node.parent() {
+        Some(parent) => parent,
+        None => return name_node,
+    };
+    while matches!(
+        node.kind(),
+        "function_declarator"
+            | "pointer_declarator"
+            | "reference_declarator"
+            | "qualified_identifier"
+    ) {
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    node
+}
 
-    Ok(symbols)
+/// Converte o nome de uma captura (`@function.name`, ...) no prefixo do tipo de símbolo
+fn symbol_kind_for_capture(capture_name: &str) -> &'static str {
+    match capture_name {
+        name if name.contains("function") => "fn",
+        name if name.contains("struct") => "struct",
+        name if name.contains("class") => "class",
+        name if name.contains("enum") => "enum",
+        name if name.contains("trait") => "trait",
+        name if name.contains("interface") => "interface",
+        name if name.contains("type") => "type",
+        name if name.contains("impl") => "impl",
+        name if name.contains("mod") => "mod",
+        name if name.contains("const") => "const",
+        name if name.contains("static") => "static",
+        name if name.contains("method") => "method",
+        name if name.contains("variable") => "var",
+        name if name.contains("import") => "import",
+        name if name.contains("record") => "record",
+        name if name.contains("namespace") => "namespace",
+        name if name.contains("protocol") => "protocol",
+        name if name.contains("extension") => "extension",
+        name if name.contains("export") => "export",
+        name if name.contains("macro") => "macro",
+        name if name.contains("union") => "union",
+        name if name.contains("extern") => "extern",
+        _ => "",
+    }
 }
 
 /// Query para extrair símbolos do Rust
-fn get_rust_query() -> String {
+fn get_rust_query() -> &'static str {
     r#"
     (function_item
       name: (identifier) @function.name)
@@ -707,76 +2393,522 @@ fn get_rust_query() -> String {
 
     (static_item
       name: (identifier) @static.name)
+
+    (macro_definition
+      name: (identifier) @macro.name)
+
+    (type_item
+      name: (type_identifier) @type.name)
+
+    (associated_type
+      name: (type_identifier) @type.name)
+
+    (union_item
+      name: (type_identifier) @union.name)
+
+    (foreign_mod_item
+      (extern_modifier (string_literal) @extern.name))
+
+    (foreign_mod_item
+      body: (declaration_list
+        (function_signature_item
+          name: (identifier) @function.name)))
     "#
-    .to_string()
 }
 
 /// Query para extrair símbolos do JavaScript
-fn get_javascript_query() -> String {
+fn get_javascript_query() -> &'static str {
+    static QUERY: OnceLock<String> = OnceLock::new();
+    QUERY.get_or_init(|| {
+        r#"
+        (function_declaration
+          name: (identifier) @function.name)
+
+        (class_declaration
+          name: (identifier) @class.name)
+
+        (method_definition
+          name: (property_identifier) @method.name)
+
+        (variable_declarator
+          name: (identifier) @variable.name)
+
+        (generator_function_declaration
+          name: (identifier) @function.name)
+        "#
+        .to_string()
+            + get_javascript_exports_query()
+    })
+}
+
+/// Query para extrair símbolos do TypeScript
+fn get_typescript_query() -> &'static str {
+    static QUERY: OnceLock<String> = OnceLock::new();
+    QUERY.get_or_init(|| {
+        r#"
+        (function_declaration
+          name: (identifier) @function.name)
+
+        (class_declaration
+          name: (type_identifier) @class.name)
+
+        (interface_declaration
+          name: (type_identifier) @interface.name)
+
+        (type_alias_declaration
+          name: (type_identifier) @type.name)
+
+        (enum_declaration
+          name: (identifier) @enum.name)
+
+        (method_definition
+          name: (property_identifier) @method.name)
+
+        (variable_declarator
+          name: (identifier) @variable.name)
+
+        (abstract_class_declaration
+          name: (type_identifier) @class.name)
+        "#
+        .to_string()
+            + get_javascript_exports_query()
+    })
+}
+
+/// Query para os exports ES modules e CommonJS, partilhada por JavaScript e TypeScript
+fn get_javascript_exports_query() -> &'static str {
     r#"
-    (function_declaration
-      name: (identifier) @function.name)
+    (export_statement
+      (export_clause
+        (export_specifier
+          name: (identifier) @export.name
+          !alias)))
+
+    (export_statement
+      (export_clause
+        (export_specifier
+          alias: (identifier) @export.name)))
+
+    (export_statement
+      value: (identifier) @export.default)
+
+    (export_statement
+      "default" @export.default
+      value: [
+        (function_expression)
+        (arrow_function)
+        (object)
+        (class)
+        (call_expression)
+      ])
+
+    (assignment_expression
+      left: (member_expression
+        object: (identifier) @_module
+        property: (property_identifier) @_exports)
+      right: (object
+        [
+          (pair key: (property_identifier) @export.name)
+          (shorthand_property_identifier) @export.name
+          (method_definition name: (property_identifier) @export.name)
+        ])
+      (#eq? @_module "module")
+      (#eq? @_exports "exports"))
+
+    (assignment_expression
+      left: (member_expression
+        object: (identifier) @_module
+        property: (property_identifier) @_exports)
+      right: (identifier) @export.default
+      (#eq? @_module "module")
+      (#eq? @_exports "exports"))
+
+    (assignment_expression
+      left: (member_expression
+        object: (identifier) @_exports
+        property: (property_identifier) @export.name)
+      (#eq? @_exports "exports"))
+
+    (assignment_expression
+      left: (member_expression
+        object: (member_expression
+          object: (identifier) @_module
+          property: (property_identifier) @_exports)
+        property: (property_identifier) @export.name)
+      (#eq? @_module "module")
+      (#eq? @_exports "exports"))
+    "#
+}
 
-    (class_declaration
+/// Query para extrair símbolos do Python.
+/// Apenas atribuições ao nível do módulo ou da classe contam como variáveis.
+fn get_python_query() -> &'static str {
+    r#"
+    (module
+      [
+        (function_definition name: (identifier) @function.name)
+        (decorated_definition
+          definition: (function_definition name: (identifier) @function.name))
+      ])
+
+    (class_definition
+      body: (block
+        [
+          (function_definition name: (identifier) @method.name)
+          (decorated_definition
+            definition: (function_definition name: (identifier) @method.name))
+        ]))
+
+    (class_definition
       name: (identifier) @class.name)
 
-    (method_definition
-      name: (property_identifier) @method.name)
+    (module
+      (expression_statement
+        (assignment
+          left: (identifier) @variable.name)))
 
-    (variable_declarator
-      name: (identifier) @variable.name)
+    (class_definition
+      body: (block
+        (expression_statement
+          (assignment
+            left: (identifier) @variable.name))))
     "#
-    .to_string()
 }
 
-/// Query para extrair símbolos do TypeScript
-fn get_typescript_query() -> String {
+/// Query para extrair símbolos do Go
+fn get_go_query() -> &'static str {
     r#"
     (function_declaration
       name: (identifier) @function.name)
 
+    (method_declaration
+      receiver: (parameter_list
+        (parameter_declaration
+          type: [
+            (type_identifier) @method.receiver
+            (pointer_type (type_identifier) @method.receiver)
+          ]))
+      name: (field_identifier) @method.name)
+
+    (type_spec
+      name: (type_identifier) @struct.name
+      type: (struct_type))
+
+    (type_spec
+      name: (type_identifier) @interface.name
+      type: (interface_type))
+
+    (type_spec
+      name: (type_identifier) @type.name
+      type: [
+        (type_identifier)
+        (qualified_type)
+        (pointer_type)
+        (slice_type)
+        (array_type)
+        (map_type)
+        (channel_type)
+        (function_type)
+      ])
+
+    (const_spec
+      name: (identifier) @const.name)
+    "#
+}
+
+/// Query para extrair símbolos do Java
+fn get_java_query() -> &'static str {
+    r#"
     (class_declaration
-      name: (type_identifier) @class.name)
+      name: (identifier) @class.name)
 
     (interface_declaration
-      name: (type_identifier) @interface.name)
+      name: (identifier) @interface.name)
+
+    (enum_declaration
+      name: (identifier) @enum.name)
+
+    (record_declaration
+      name: (identifier) @record.name)
+
+    (method_declaration
+      name: (identifier) @method.name)
+
+    (constructor_declaration
+      name: (identifier) @method.name)
+    "#
+}
+
+/// Query para extrair símbolos do C
+fn get_c_query() -> &'static str {
+    r#"
+    (function_definition
+      declarator: (function_declarator
+        declarator: (identifier) @function.name))
+
+    (function_definition
+      declarator: (pointer_declarator
+        declarator: (function_declarator
+          declarator: (identifier) @function.name)))
+
+    (declaration
+      declarator: (function_declarator
+        declarator: (identifier) @function.name))
+
+    (struct_specifier
+      name: (type_identifier) @struct.name
+      body: (field_declaration_list))
+
+    (union_specifier
+      name: (type_identifier) @struct.name
+      body: (field_declaration_list))
 
-    (type_alias_declaration
+    (enum_specifier
+      name: (type_identifier) @enum.name
+      body: (enumerator_list))
+
+    (type_definition
+      declarator: (type_identifier) @type.name)
+    "#
+}
+
+/// Query para extrair símbolos do C++
+fn get_cpp_query() -> &'static str {
+    r#"
+    (function_definition
+      declarator: (function_declarator
+        declarator: (identifier) @function.name))
+
+    (function_definition
+      declarator: (pointer_declarator
+        declarator: (function_declarator
+          declarator: (identifier) @function.name)))
+
+    (function_definition
+      declarator: (reference_declarator
+        (function_declarator
+          declarator: (identifier) @function.name)))
+
+    (declaration
+      declarator: (function_declarator
+        declarator: (identifier) @function.name))
+
+    (function_definition
+      declarator: (function_declarator
+        declarator: (qualified_identifier) @method.name))
+
+    (function_definition
+      declarator: (function_declarator
+        declarator: (field_identifier) @method.name))
+
+    (field_declaration
+      declarator: (function_declarator
+        declarator: (field_identifier) @method.name))
+
+    (class_specifier
+      name: (type_identifier) @class.name
+      body: (field_declaration_list))
+
+    (struct_specifier
+      name: (type_identifier) @struct.name
+      body: (field_declaration_list))
+
+    (enum_specifier
+      name: (type_identifier) @enum.name
+      body: (enumerator_list))
+
+    (namespace_definition
+      name: (namespace_identifier) @namespace.name)
+
+    (type_definition
+      declarator: (type_identifier) @type.name)
+
+    (alias_declaration
       name: (type_identifier) @type.name)
+    "#
+}
+
+/// Query para extrair símbolos do C#
+fn get_csharp_query() -> &'static str {
+    r#"
+    (namespace_declaration
+      name: (_) @namespace.name)
+
+    (file_scoped_namespace_declaration
+      name: (_) @namespace.name)
+
+    (class_declaration
+      name: (identifier) @class.name)
+
+    (struct_declaration
+      name: (identifier) @struct.name)
+
+    (interface_declaration
+      name: (identifier) @interface.name)
+
+    (record_declaration
+      name: (identifier) @record.name)
+
+    (record_struct_declaration
+      name: (identifier) @record.name)
 
     (enum_declaration
       name: (identifier) @enum.name)
 
-    (method_definition
-      name: (property_identifier) @method.name)
+    (delegate_declaration
+      name: (identifier) @type.name)
+
+    (method_declaration
+      name: (identifier) @method.name)
 
-    (variable_declarator
-      name: (identifier) @variable.name)
+    (constructor_declaration
+      name: (identifier) @method.name)
+    "#
+}
+
+/// Query para extrair símbolos do Ruby
+fn get_ruby_query() -> &'static str {
+    r#"
+    (module
+      name: [
+        (constant) @module.name
+        (scope_resolution name: (constant) @module.name)
+      ])
+
+    (class
+      name: [
+        (constant) @class.name
+        (scope_resolution name: (constant) @class.name)
+      ])
+
+    (method
+      name: (_) @method.name)
+
+    (singleton_method
+      object: (_) @method.receiver
+      name: (_) @method.name)
+
+    (program
+      (assignment
+        left: (constant) @const.name))
     "#
-    .to_string()
 }
 
-/// Query para extrair símbolos do Python
-fn get_python_query() -> String {
+/// Query para extrair símbolos do PHP
+fn get_php_query() -> &'static str {
     r#"
+    (namespace_definition
+      name: (namespace_name) @namespace.name)
+
+    (class_declaration
+      name: (name) @class.name)
+
+    (interface_declaration
+      name: (name) @interface.name)
+
+    (trait_declaration
+      name: (name) @trait.name)
+
+    (enum_declaration
+      name: (name) @enum.name)
+
     (function_definition
-      name: (identifier) @function.name)
+      name: (name) @function.name)
 
-    (class_definition
-      name: (identifier) @class.name)
+    (method_declaration
+      name: (name) @method.name)
+
+    (const_declaration
+      (const_element
+        (name) @const.name))
+    "#
+}
+
+/// Query para extrair símbolos do Kotlin
+fn get_kotlin_query() -> &'static str {
+    r#"
+    (class_declaration
+      "class"
+      (type_identifier) @class.name)
+
+    (class_declaration
+      "interface"
+      (type_identifier) @interface.name)
+
+    (object_declaration
+      (type_identifier) @class.name)
+
+    (type_alias
+      (type_identifier) @type.name)
+
+    (source_file
+      (function_declaration
+        (simple_identifier) @function.name))
+
+    (class_body
+      (function_declaration
+        (simple_identifier) @method.name))
+
+    (enum_class_body
+      (function_declaration
+        (simple_identifier) @method.name))
+    "#
+}
+
+/// Query para extrair símbolos do Swift
+fn get_swift_query() -> &'static str {
+    r#"
+    (class_declaration
+      declaration_kind: "class"
+      name: (type_identifier) @class.name)
+
+    (class_declaration
+      declaration_kind: "actor"
+      name: (type_identifier) @class.name)
+
+    (class_declaration
+      declaration_kind: "struct"
+      name: (type_identifier) @struct.name)
+
+    (class_declaration
+      declaration_kind: "enum"
+      name: (type_identifier) @enum.name)
+
+    (class_declaration
+      declaration_kind: "extension"
+      name: (user_type
+        (type_identifier) @extension.name))
+
+    (protocol_declaration
+      name: (type_identifier) @protocol.name)
+
+    (typealias_declaration
+      name: (type_identifier) @type.name)
 
-    (assignment
-      left: (identifier) @variable.name)
+    (source_file
+      (function_declaration
+        name: (simple_identifier) @function.name))
 
-    (import_statement
-      name: (dotted_name
-        (identifier) @import.name))
+    (class_body
+      (function_declaration
+        name: (simple_identifier) @method.name))
+
+    (enum_class_body
+      (function_declaration
+        name: (simple_identifier) @method.name))
+
+    (protocol_body
+      (protocol_function_declaration
+        name: (simple_identifier) @method.name))
+    "#
+}
 
-    (import_from_statement
-      name: (dotted_name
-        (identifier) @import.name))
+/// Query para shell (sh/bash/zsh): definições de funções
+fn get_shell_query() -> &'static str {
+    r#"
+    (function_definition
+      name: (word) @function.name)
     "#
-    .to_string()
 }
 
 fn main() -> Result<()> {
@@ -786,13 +2918,21 @@ fn main() -> Result<()> {
     eprintln!("📡 Protocol: JSON-RPC over stdin/stdout");
     eprintln!("🔧 Tools available:");
     eprintln!("   - get_workspace_context: Analyze workspace structure and code symbols");
-    eprintln!("🏗️  Supported languages: Rust, JavaScript, TypeScript, Python");
+    eprintln!("   - get_project_overview: Summarize the README and docs/ headings");
+    eprintln!("   - get_symbol_diff: Report symbols added, removed or modified vs a git ref");
+    eprintln!("   - get_database_schema: Merge SQL migrations into tables, indexes and views");
+    eprintln!("   - get_tests: List tests and the source modules they cover");
+    eprintln!("   - get_relevant_context: Rank files, symbols and snippets for a question");
+    eprintln!("   - semantic_search: Find the symbols nearest to a description");
+    eprintln!(
+        "🏗️  Supported languages: Rust, JavaScript, TypeScript, Python, Go, Java, C, C++, C#, Ruby, PHP, Kotlin, Swift, Shell, Vue, Svelte"
+    );
     eprintln!(
         "📁 Working directory: {:?}",
         std::env::current_dir().unwrap_or_default()
     );
     eprintln!("✅ Server ready - waiting for MCP client connection...");
-    eprintln!("");
+    eprintln!();
 
     // Print Zed configuration example
     eprintln!("📋 To use with Zed, add this to your settings.json:");
@@ -810,10 +2950,11 @@ fn main() -> Result<()> {
     eprintln!("  }}");
     eprintln!("}}");
     eprintln!("════════════════════════════════════════════════════════════");
-    eprintln!("");
+    eprintln!();
 
-    // Criar o handler RPC
-    let rpc_handler = RpcHandler::new();
+    // Criar o handler RPC, partilhando os workspaces observados entre os métodos
+    let workspaces = Arc::new(Workspaces::default());
+    let rpc_handler = RpcHandler::new(workspaces.clone());
 
     // Configurar o servidor de IO
     let mut io = IoHandler::new();
@@ -822,17 +2963,30 @@ fn main() -> Result<()> {
     io.add_sync_method("initialize", move |params| rpc_handler.initialize(params));
 
     // Registar o método list_tools
-    let rpc_handler_tools = RpcHandler::new();
+    let rpc_handler_tools = RpcHandler::new(workspaces.clone());
     io.add_sync_method("tools/list", move |params| {
         rpc_handler_tools.list_tools(params)
     });
 
     // Registar o método execute_tool
-    let rpc_handler_clone = RpcHandler::new();
+    let rpc_handler_clone = RpcHandler::new(workspaces.clone());
     io.add_sync_method("tools/call", move |params| {
         rpc_handler_clone.execute_tool(params)
     });
 
+    // Registar as notificações de documentos abertos no editor
+    let workspaces_open = workspaces.clone();
+    io.add_notification("textDocument/didOpen", move |params| {
+        documents::did_open(&workspaces_open, params)
+    });
+    let workspaces_change = workspaces.clone();
+    io.add_notification("textDocument/didChange", move |params| {
+        documents::did_change(&workspaces_change, params)
+    });
+    io.add_notification("textDocument/didClose", move |params| {
+        documents::did_close(&workspaces, params)
+    });
+
     // Criar reader/writer para stdin/stdout
     let stdin = io::stdin();
     let mut stdout = io::stdout();
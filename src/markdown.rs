@@ -0,0 +1,323 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::Symbol;
+
+/// Nível máximo de cabeçalho incluído no outline (H1–H3)
+const MAX_HEADING_LEVEL: usize = 3;
+
+/// Cabeçalho Markdown com o seu nível e linha (base 1)
+struct Heading {
+    level: usize,
+    text: String,
+    line: usize,
+    byte: usize,
+}
+
+/// Verifica se o ficheiro é Markdown pela extensão
+pub(crate) fn is_markdown_path(file_path: &Path) -> bool {
+    matches!(
+        file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref(),
+        Some("md") | Some("markdown")
+    )
+}
+
+/// Extrai o outline de cabeçalhos (H1–H3) como símbolos `h1`/`h2`/`h3`,
+/// pela ordem em que aparecem no documento
+pub(crate) fn extract_markdown_symbols(content: &str) -> Vec<Symbol> {
+    let headings = parse_headings(content);
+    let total_lines = content.lines().count().max(1);
+
+    headings
+        .iter()
+        .enumerate()
+        .filter(|(_, heading)| heading.level <= MAX_HEADING_LEVEL)
+        .map(|(i, heading)| {
+            // A secção termina antes do próximo cabeçalho de nível igual ou superior
+            let next = headings[i + 1..]
+                .iter()
+                .find(|other| other.level <= heading.level);
+            let (end_line, end_byte) = match next {
+                Some(next) => (next.line - 1, next.byte),
+                None => (total_lines, content.len()),
+            };
+            Symbol {
+                kind: format!("h{}", heading.level),
+                name: heading.text.clone(),
+                start_line: heading.line,
+                end_line: end_line.max(heading.line),
+                start_byte: heading.byte,
+                end_byte,
+                detail: None,
+                container: None,
+            }
+        })
+        .collect()
+}
+
+/// Percorre o documento e devolve todos os cabeçalhos ATX (`#`) e setext (`===`/`---`),
+/// ignorando blocos de código delimitados
+fn parse_headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut in_code_block = false;
+    let mut previous: Option<(&str, usize, usize)> = None;
+    let mut byte = 0;
+
+    for (index, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line_start = byte;
+        byte += raw_line.len();
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            previous = None;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if let Some(level) = atx_heading_level(trimmed) {
+            let text = trimmed[level..].trim().trim_end_matches('#').trim();
+            if !text.is_empty() {
+                headings.push(Heading {
+                    level,
+                    text: text.to_string(),
+                    line: index + 1,
+                    byte: line_start,
+                });
+            }
+            previous = None;
+            continue;
+        }
+
+        // Setext: a linha anterior é o texto e esta é o sublinhado
+        if let Some((text, text_line, text_byte)) = previous
+            && !trimmed.is_empty()
+            && (trimmed.chars().all(|c| c == '=') || trimmed.chars().all(|c| c == '-'))
+        {
+            headings.push(Heading {
+                level: if trimmed.starts_with('=') { 1 } else { 2 },
+                text: text.to_string(),
+                line: text_line,
+                byte: text_byte,
+            });
+            previous = None;
+            continue;
+        }
+
+        previous = if trimmed.is_empty() || line.starts_with("    ") {
+            None
+        } else {
+            Some((trimmed, index + 1, line_start))
+        };
+    }
+
+    headings
+}
+
+/// Nível de um cabeçalho ATX (`## Título` -> 2), se a linha for um
+fn atx_heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&level)
+        && line[level..]
+            .chars()
+            .next()
+            .is_none_or(|c| c.is_whitespace())
+    {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// Constrói a visão geral do projeto: título, parágrafos iniciais e secções do README,
+/// seguidos do índice de cabeçalhos dos ficheiros em `docs/`
+pub(crate) fn format_project_overview(
+    workspace_dir: &Path,
+    max_paragraphs: usize,
+) -> Result<String> {
+    let mut result = String::new();
+    result.push_str("📘 Project Overview\n");
+    result.push_str("══════════════════════════════════\n\n");
+
+    match find_readme(workspace_dir) {
+        Some(readme_path) => {
+            let content = fs::read_to_string(&readme_path)?;
+            let headings = parse_headings(&content);
+
+            let title = headings
+                .iter()
+                .find(|heading| heading.level == 1)
+                .map(|heading| heading.text.clone())
+                .unwrap_or_else(|| {
+                    workspace_dir
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                });
+            let readme_name = readme_path
+                .strip_prefix(workspace_dir)
+                .unwrap_or(&readme_path)
+                .display()
+                .to_string();
+            result.push_str(&format!("# {}\n", title));
+            result.push_str(&format!("(from {})\n\n", readme_name));
+
+            for paragraph in intro_paragraphs(&content, max_paragraphs) {
+                result.push_str(&paragraph);
+                result.push_str("\n\n");
+            }
+
+            let sections: Vec<&Heading> = headings
+                .iter()
+                .filter(|heading| heading.level > 1 && heading.level <= MAX_HEADING_LEVEL)
+                .collect();
+            if !sections.is_empty() {
+                result.push_str("📑 Sections:\n");
+                for heading in sections {
+                    let indent = "  ".repeat(heading.level - 1);
+                    result.push_str(&format!("{}• {}\n", indent, heading.text));
+                }
+                result.push('\n');
+            }
+        }
+        None => {
+            result.push_str("⚠️  No README found at the workspace root\n\n");
+        }
+    }
+
+    let docs = collect_docs(workspace_dir);
+    if !docs.is_empty() {
+        result.push_str("📚 Docs:\n");
+        for doc in &docs {
+            let relative = doc.strip_prefix(workspace_dir).unwrap_or(doc);
+            result.push_str(&format!("{}\n", relative.display()));
+            if let Ok(content) = fs::read_to_string(doc) {
+                for heading in parse_headings(&content)
+                    .iter()
+                    .filter(|heading| heading.level <= MAX_HEADING_LEVEL)
+                {
+                    let indent = "  ".repeat(heading.level);
+                    result.push_str(&format!("{}• {}\n", indent, heading.text));
+                }
+            }
+        }
+        result.push('\n');
+    }
+
+    result.push_str(&format!("📊 Root: {}\n", workspace_dir.display()));
+
+    Ok(result)
+}
+
+/// Procura o README no topo do workspace (README.md, readme.md, README, ...)
+fn find_readme(workspace_dir: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = fs::read_dir(workspace_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.eq_ignore_ascii_case("readme"))
+        })
+        .collect();
+
+    // Preferir Markdown a outros formatos
+    candidates.sort_by_key(|path| (!is_markdown_path(path), path.clone()));
+    candidates.into_iter().next()
+}
+
+/// Primeiros parágrafos de texto do README, ignorando cabeçalhos, badges,
+/// HTML e blocos de código
+fn intro_paragraphs(content: &str, max_paragraphs: usize) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_code_block = false;
+    let mut seen_section = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let is_heading = atx_heading_level(trimmed).is_some();
+        // A introdução termina na primeira secção (H2 ou inferior)
+        if atx_heading_level(trimmed).is_some_and(|level| level > 1) {
+            seen_section = true;
+        }
+
+        // Sublinhado setext: a linha acumulada era um cabeçalho e não um parágrafo
+        if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=' || c == '-') {
+            if current.len() == 1 {
+                current.clear();
+                seen_section |= trimmed.starts_with('-');
+            }
+            if seen_section {
+                break;
+            }
+            continue;
+        }
+
+        let is_noise =
+            trimmed.starts_with("[![") || trimmed.starts_with("![") || trimmed.starts_with('<');
+
+        if trimmed.is_empty() || is_heading || is_noise {
+            if !current.is_empty() {
+                paragraphs.push(current.join(" "));
+                current.clear();
+            }
+            if seen_section || paragraphs.len() >= max_paragraphs {
+                break;
+            }
+            continue;
+        }
+
+        current.push(trimmed);
+    }
+
+    if !current.is_empty() && paragraphs.len() < max_paragraphs {
+        paragraphs.push(current.join(" "));
+    }
+
+    paragraphs.truncate(max_paragraphs);
+    paragraphs
+}
+
+/// Ficheiros Markdown dentro de `docs/` ou `doc/`, ordenados por caminho
+fn collect_docs(workspace_dir: &Path) -> Vec<PathBuf> {
+    let mut docs = Vec::new();
+
+    for dir_name in ["docs", "doc"] {
+        let dir = workspace_dir.join(dir_name);
+        if !dir.is_dir() {
+            continue;
+        }
+        docs.extend(
+            WalkDir::new(&dir)
+                .max_depth(4)
+                .into_iter()
+                .flatten()
+                .map(|entry| entry.into_path())
+                .filter(|path| path.is_file() && is_markdown_path(path)),
+        );
+    }
+
+    docs.sort();
+    docs
+}
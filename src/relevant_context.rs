@@ -0,0 +1,508 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::search_terms::{query_terms, terms};
+use crate::symbol_index::SymbolIndex;
+use crate::token_budget::estimate_tokens;
+use crate::{Symbol, format_symbol};
+
+/// Bytes iniciais de cada definição cujos identificadores também contam na pesquisa
+const BODY_SCAN_BYTES: usize = 2000;
+
+/// Linhas de documentação consideradas acima (ou no início) de cada definição
+const MAX_DOC_LINES: usize = 12;
+
+/// Comprimento máximo dos resumos da documentação mostrados na árvore
+const MAX_SUMMARY_CHARS: usize = 80;
+
+/// Linhas mostradas em cada excerto de código
+const MAX_SNIPPET_LINES: usize = 30;
+
+/// Símbolos mostrados por ficheiro na lista de resultados
+const MAX_SYMBOLS_PER_FILE: usize = 5;
+
+/// Símbolos com pontuação abaixo desta fração da melhor não são mostrados
+const MIN_RELATIVE_SCORE: f64 = 0.25;
+
+/// Parâmetros do BM25
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+/// Peso de cada campo nos termos de um símbolo
+const NAME_WEIGHT: f64 = 3.0;
+const ENCLOSING_WEIGHT: f64 = 1.5;
+const DOC_WEIGHT: f64 = 1.0;
+const PATH_WEIGHT: f64 = 0.5;
+const BODY_WEIGHT: f64 = 0.3;
+
+/// Símbolo e o texto à volta onde as pesquisas procuram
+pub(crate) struct SymbolContext {
+    /// Caminho relativo ao workspace
+    pub(crate) path: PathBuf,
+    pub(crate) symbol: Symbol,
+    /// Símbolo onde está definido (`impl User`, `class UserService`)
+    pub(crate) enclosing: Option<String>,
+    /// Comentário de documentação, sem os marcadores de comentário
+    pub(crate) doc: String,
+    /// Início da definição, até `BODY_SCAN_BYTES`
+    pub(crate) body: String,
+    /// Primeira linha (base 1) do excerto, incluindo a documentação e os atributos
+    pub(crate) snippet_start: usize,
+}
+
+impl SymbolContext {
+    /// Termos do símbolo com o peso de cada campo
+    pub(crate) fn weighted_terms(&self) -> HashMap<String, f64> {
+        let mut weighted = HashMap::new();
+        let path = self.path.with_extension("");
+        let fields = [
+            (self.symbol.name.as_str(), NAME_WEIGHT),
+            (
+                self.enclosing.as_deref().unwrap_or_default(),
+                ENCLOSING_WEIGHT,
+            ),
+            (self.doc.as_str(), DOC_WEIGHT),
+            (&*path.to_string_lossy(), PATH_WEIGHT),
+            (self.body.as_str(), BODY_WEIGHT),
+        ];
+        for (text, weight) in fields {
+            for term in terms(text) {
+                *weighted.entry(term).or_insert(0.0) += weight;
+            }
+        }
+        weighted
+    }
+}
+
+/// Símbolos indexados dos ficheiros com a documentação, o símbolo envolvente e o
+/// início do corpo de cada um
+pub(crate) fn symbol_contexts(
+    workspace_dir: &Path,
+    files: &[PathBuf],
+    index: &SymbolIndex,
+) -> Vec<SymbolContext> {
+    let mut contexts = Vec::new();
+    for file in files {
+        // Ficheiros binários, minificados ou gerados não entram na pesquisa
+        if index.skip_reason(file).is_some() {
+            continue;
+        }
+        let Some(Ok(symbols)) = index.symbols(file) else {
+            continue;
+        };
+        if symbols.is_empty() {
+            continue;
+        }
+        let Some(content) = index.document_text(file) else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let path = file.strip_prefix(workspace_dir).unwrap_or(file);
+
+        for symbol in symbols {
+            let (doc, snippet_start) = doc_comment(&lines, symbol);
+            let mut body_end = symbol.end_byte.min(symbol.start_byte + BODY_SCAN_BYTES);
+            while !content.is_char_boundary(body_end) {
+                body_end -= 1;
+            }
+            contexts.push(SymbolContext {
+                path: path.to_path_buf(),
+                symbol: symbol.clone(),
+                enclosing: enclosing_symbol(symbol, symbols),
+                doc,
+                body: content
+                    .get(symbol.start_byte..body_end)
+                    .unwrap_or_default()
+                    .to_string(),
+                snippet_start,
+            });
+        }
+    }
+    contexts
+}
+
+/// Classe do símbolo ou, na sua falta, o menor símbolo que contém a definição
+fn enclosing_symbol(symbol: &Symbol, symbols: &[Symbol]) -> Option<String> {
+    if let Some(container) = &symbol.container {
+        return Some(container.clone());
+    }
+    symbols
+        .iter()
+        .filter(|other| {
+            other.start_byte <= symbol.start_byte
+                && other.end_byte >= symbol.end_byte
+                && (other.start_byte, other.end_byte) != (symbol.start_byte, symbol.end_byte)
+        })
+        .min_by_key(|other| other.end_byte - other.start_byte)
+        .map(|other| format!("{} {}", other.kind, other.name).trim().to_string())
+}
+
+/// Primeira frase da documentação do símbolo, encurtada para `MAX_SUMMARY_CHARS`
+pub(crate) fn doc_summary(lines: &[&str], symbol: &Symbol) -> Option<String> {
+    let (doc, _) = doc_comment(lines, symbol);
+    // O fim da frase é o primeiro ponto seguido de espaço que não faz parte de `...`
+    let end = doc
+        .match_indices(". ")
+        .map(|(end, _)| end)
+        .find(|end| !doc[..*end].ends_with('.'));
+    let sentence = match end {
+        Some(end) => &doc[..=end],
+        None => doc.as_str(),
+    };
+    if sentence.is_empty() {
+        return None;
+    }
+    if sentence.chars().count() <= MAX_SUMMARY_CHARS {
+        return Some(sentence.to_string());
+    }
+    let shortened: String = sentence.chars().take(MAX_SUMMARY_CHARS - 1).collect();
+    Some(format!("{}…", shortened.trim_end()))
+}
+
+/// Comentários imediatamente acima da definição (`///`, `//`, `#`, JSDoc), saltando
+/// atributos, ou a docstring Python logo a seguir. Devolve também a linha onde começa.
+fn doc_comment(lines: &[&str], symbol: &Symbol) -> (String, usize) {
+    let mut doc = Vec::new();
+    let mut snippet_start = symbol.start_line;
+
+    let mut line = symbol.start_line.saturating_sub(1);
+    while line > 0 && doc.len() < MAX_DOC_LINES {
+        let text = lines.get(line - 1).map_or("", |text| text.trim());
+        if text.starts_with("#[") || text.starts_with('@') {
+            // Atributos Rust e decorators entre a documentação e a definição
+        } else if ["//", "#", "/*", "*", "--"]
+            .iter()
+            .any(|prefix| text.starts_with(prefix))
+        {
+            doc.push(text.trim_start_matches(['/', '*', '#', '!', '-']).trim());
+        } else {
+            break;
+        }
+        snippet_start = line;
+        line -= 1;
+    }
+    doc.reverse();
+
+    // Docstring Python na primeira linha do corpo
+    if doc.is_empty()
+        && let Some(first) = lines.get(symbol.start_line).map(|text| text.trim())
+        && let Some(quote) = ["\"\"\"", "'''"]
+            .into_iter()
+            .find(|quote| first.starts_with(quote))
+    {
+        for text in lines[symbol.start_line..].iter().take(MAX_DOC_LINES) {
+            let text = text.trim();
+            doc.push(text.trim_matches(|c| c == '"' || c == '\''));
+            if text.len() > quote.len() && text.ends_with(quote) {
+                break;
+            }
+        }
+    }
+
+    let doc = doc
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    (doc, snippet_start)
+}
+
+/// Pontuação BM25 de cada documento (termos com peso) para os termos da pesquisa
+fn bm25_scores(documents: &[HashMap<String, f64>], query: &[String]) -> Vec<f64> {
+    let count = documents.len() as f64;
+    let lengths: Vec<f64> = documents
+        .iter()
+        .map(|document| document.values().sum())
+        .collect();
+    let average_length = (lengths.iter().sum::<f64>() / count.max(1.0)).max(f64::EPSILON);
+
+    let unique: HashSet<&String> = query.iter().collect();
+    let idf: Vec<(&String, f64)> = unique
+        .into_iter()
+        .map(|term| {
+            let frequency = documents
+                .iter()
+                .filter(|document| document.contains_key(term))
+                .count() as f64;
+            (
+                term,
+                (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln(),
+            )
+        })
+        .collect();
+
+    documents
+        .iter()
+        .zip(&lengths)
+        .map(|(document, length)| {
+            let normalization = BM25_K1 * (1.0 - BM25_B + BM25_B * length / average_length);
+            idf.iter()
+                .filter_map(|(term, idf)| {
+                    let frequency = document.get(*term)?;
+                    Some(idf * frequency * (BM25_K1 + 1.0) / (frequency + normalization))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Ficheiros e símbolos mais relevantes para a pergunta, seguidos de excertos de
+/// código até esgotar `max_tokens`
+pub(crate) fn format_relevant_context(
+    workspace_dir: &Path,
+    question: &str,
+    files: &[PathBuf],
+    index: &SymbolIndex,
+    max_files: usize,
+    max_tokens: usize,
+    truncated: Option<&str>,
+) -> String {
+    let mut result = String::new();
+    result.push_str("🔎 Relevant Context\n");
+    result.push_str("══════════════════════════════════\n");
+    result.push_str(&format!("❓ {}\n\n", question.trim()));
+
+    let query = query_terms(question);
+    if query.is_empty() {
+        result.push_str("No searchable terms in the question.\n");
+        return result;
+    }
+
+    let contexts = symbol_contexts(workspace_dir, files, index);
+    let documents: Vec<HashMap<String, f64>> =
+        contexts.iter().map(SymbolContext::weighted_terms).collect();
+    let scores = bm25_scores(&documents, &query);
+
+    // Símbolos relevantes, do mais para o menos pontuado
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    let mut matches: Vec<usize> = (0..contexts.len())
+        .filter(|position| {
+            scores[*position] > 0.0 && scores[*position] >= best * MIN_RELATIVE_SCORE
+        })
+        .collect();
+    matches.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+
+    // Cada ficheiro vale o seu melhor símbolo mais uma parte dos seguintes
+    let mut by_file: Vec<(&Path, Vec<usize>)> = Vec::new();
+    for position in &matches {
+        let path = contexts[*position].path.as_path();
+        match by_file.iter_mut().find(|(file, _)| *file == path) {
+            Some((_, positions)) => positions.push(*position),
+            None => by_file.push((path, vec![*position])),
+        }
+    }
+    let file_score = |positions: &[usize]| -> f64 {
+        scores[positions[0]]
+            + 0.25
+                * positions[1..]
+                    .iter()
+                    .take(4)
+                    .map(|position| scores[*position])
+                    .sum::<f64>()
+    };
+    by_file.sort_by(|(a_path, a), (b_path, b)| {
+        file_score(b)
+            .total_cmp(&file_score(a))
+            .then_with(|| a_path.cmp(b_path))
+    });
+    let total_files = by_file.len();
+    by_file.truncate(max_files);
+
+    if by_file.is_empty() {
+        result.push_str("No matching symbols found.\n");
+    }
+    let file_blocks: Vec<String> = by_file
+        .iter()
+        .map(|(path, positions)| {
+            let mut block = format!("📄 {}\n", path.display());
+            let shown: Vec<&usize> = positions.iter().take(MAX_SYMBOLS_PER_FILE).collect();
+            for (i, position) in shown.iter().enumerate() {
+                let context = &contexts[**position];
+                let marker = if i + 1 == shown.len() && positions.len() <= MAX_SYMBOLS_PER_FILE {
+                    "└─"
+                } else {
+                    "├─"
+                };
+                let enclosing = context
+                    .enclosing
+                    .as_ref()
+                    .map(|enclosing| format!(" in {}", enclosing))
+                    .unwrap_or_default();
+                block.push_str(&format!(
+                    "   {} {} (L{}-{}){}\n",
+                    marker,
+                    format_symbol(&context.symbol.to_string()),
+                    context.symbol.start_line,
+                    context.symbol.end_line,
+                    enclosing
+                ));
+            }
+            if positions.len() > MAX_SYMBOLS_PER_FILE {
+                block.push_str(&format!(
+                    "   └─ ... ({} more matching symbols)\n",
+                    positions.len() - MAX_SYMBOLS_PER_FILE
+                ));
+            }
+            block
+        })
+        .collect();
+
+    let summary = |files_shown: usize,
+                   omitted_files: usize,
+                   snippets_shown: usize,
+                   omitted_snippets: usize|
+     -> String {
+        let mut summary = format!(
+            "\n📊 Summary:\n\
+             • {} matching symbols in {} files\n\
+             • {} files shown, {} snippets\n",
+            matches.len(),
+            total_files,
+            files_shown,
+            snippets_shown
+        );
+        if omitted_files > 0 {
+            summary.push_str(&format!(
+                "• {} files omitted (max_tokens = {})\n",
+                omitted_files, max_tokens
+            ));
+        }
+        if omitted_snippets > 0 {
+            summary.push_str(&format!(
+                "• {} snippets omitted (max_tokens = {})\n",
+                omitted_snippets, max_tokens
+            ));
+        }
+        if let Some(reason) = truncated {
+            summary.push_str(&format!("• Truncated due to time budget: {}\n", reason));
+        }
+        summary.push_str(&format!("• Terms: {}\n", query.join(", ")));
+        summary
+    };
+    let tokens_line = |tokens: usize| format!("• ~{} tokens\n", tokens);
+
+    // O cabeçalho e o resumo (com as contagens no máximo) ficam sempre reservados;
+    // a soma das estimativas das partes nunca é menor que a do texto inteiro
+    let reserved = estimate_tokens(&result)
+        + estimate_tokens(&summary(
+            by_file.len(),
+            by_file.len(),
+            matches.len(),
+            matches.len(),
+        ))
+        + estimate_tokens(&tokens_line(max_tokens));
+    let list_tokens = |files_shown: usize| -> usize {
+        file_blocks[..files_shown]
+            .iter()
+            .map(|block| estimate_tokens(block))
+            .sum()
+    };
+
+    // Se só a lista já excede o orçamento, saem os ficheiros menos relevantes
+    let mut files_shown = by_file.len();
+    while files_shown > 0 && reserved + list_tokens(files_shown) > max_tokens {
+        files_shown -= 1;
+    }
+    for block in &file_blocks[..files_shown] {
+        result.push_str(block);
+    }
+
+    // Excertos dos melhores símbolos enquanto couberem no orçamento
+    let selected_files: HashSet<&Path> = by_file[..files_shown]
+        .iter()
+        .map(|(path, _)| *path)
+        .collect();
+    let candidates: Vec<&SymbolContext> = matches
+        .iter()
+        .map(|position| &contexts[*position])
+        .filter(|context| selected_files.contains(context.path.as_path()))
+        .collect();
+    let snippets_header = "\n📝 Snippets:\n";
+    let mut used = reserved + list_tokens(files_shown) + estimate_tokens(snippets_header);
+    let mut snippets = String::new();
+    let mut shown: Vec<&SymbolContext> = Vec::new();
+    let mut omitted = 0;
+    for context in candidates {
+        // Um excerto que já contém este símbolo (ou está contido nele) não se repete
+        let overlaps = shown.iter().any(|other| {
+            other.path == context.path
+                && other.snippet_start <= context.symbol.end_line
+                && context.snippet_start <= other.symbol.end_line
+        });
+        if overlaps {
+            continue;
+        }
+        let Some(snippet) = format_snippet(workspace_dir, index, context) else {
+            continue;
+        };
+        let snippet_tokens = estimate_tokens(&snippet);
+        if used + snippet_tokens > max_tokens {
+            omitted += 1;
+            continue;
+        }
+        used += snippet_tokens;
+        snippets.push_str(&snippet);
+        shown.push(context);
+    }
+    if !snippets.is_empty() {
+        result.push_str(snippets_header);
+        result.push_str(&snippets);
+    }
+
+    result.push_str(&summary(
+        files_shown,
+        by_file.len() - files_shown,
+        shown.len(),
+        omitted,
+    ));
+    let tokens = estimate_tokens(&result);
+    result.push_str(&tokens_line(tokens));
+    result
+}
+
+/// Código do símbolo, da documentação até ao fim da definição (ou `MAX_SNIPPET_LINES`)
+fn format_snippet(
+    workspace_dir: &Path,
+    index: &SymbolIndex,
+    context: &SymbolContext,
+) -> Option<String> {
+    let file = workspace_dir.join(&context.path);
+    if index.skip_reason(&file).is_some() {
+        return None;
+    }
+    let content = index.document_text(&file)?;
+    let lines: Vec<&str> = content
+        .lines()
+        .skip(context.snippet_start.saturating_sub(1))
+        .take(context.symbol.end_line + 1 - context.snippet_start)
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let language = context
+        .path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut snippet = format!(
+        "\n── {}:{}-{} ({})\n```{}\n",
+        context.path.display(),
+        context.snippet_start,
+        context.symbol.end_line,
+        context.symbol,
+        language
+    );
+    for line in lines.iter().take(MAX_SNIPPET_LINES) {
+        snippet.push_str(line);
+        snippet.push('\n');
+    }
+    if lines.len() > MAX_SNIPPET_LINES {
+        snippet.push_str(&format!(
+            "… ({} more lines)\n",
+            lines.len() - MAX_SNIPPET_LINES
+        ));
+    }
+    snippet.push_str("```\n");
+    Some(snippet)
+}
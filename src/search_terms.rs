@@ -0,0 +1,108 @@
+/// Palavras de ligação das perguntas que não ajudam a encontrar código
+const STOP_WORDS: [&str; 40] = [
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "do", "does", "for", "from", "how",
+    "i", "in", "is", "it", "of", "on", "or", "our", "that", "the", "this", "to", "we", "what",
+    "when", "where", "which", "who", "why", "with", "work", "works", "you", "self", "me", "there",
+];
+
+/// Sufixos removidos para que variantes da mesma palavra coincidam
+/// (`creation`, `created`, `create` -> `creat`), do mais longo para o mais curto
+const SUFFIXES: [&str; 9] = ["ions", "ings", "ion", "ing", "ers", "ed", "er", "es", "s"];
+
+/// Comprimento mínimo do radical depois de remover um sufixo
+const MIN_STEM_LEN: usize = 3;
+
+/// Termos de pesquisa de um texto: identificadores separados em palavras
+/// (`createUser`, `create_user`, `HTTPServer` -> `creat`, `user`, `http`, `serv`),
+/// em minúsculas e reduzidos ao radical
+pub(crate) fn terms(text: &str) -> Vec<String> {
+    words(text).iter().map(|word| stem(word)).collect()
+}
+
+/// Termos de uma pergunta, sem as palavras de ligação
+pub(crate) fn query_terms(question: &str) -> Vec<String> {
+    words(question)
+        .iter()
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(word))
+        .collect()
+}
+
+/// Palavras em minúsculas, separando identificadores em camelCase e snake_case
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for token in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = token.chars().collect();
+        let mut start = 0;
+        for index in 1..chars.len() {
+            let (previous, current) = (chars[index - 1], chars[index]);
+            let next_is_lower = chars.get(index + 1).is_some_and(|c| c.is_lowercase());
+            // `userId` e `HTTPServer` (fim de uma sigla seguida de uma palavra)
+            let boundary = (previous.is_lowercase() && current.is_uppercase())
+                || (previous.is_uppercase() && current.is_uppercase() && next_is_lower)
+                || (previous.is_alphabetic() != current.is_alphabetic());
+            if boundary {
+                push_word(&chars[start..index], &mut words);
+                start = index;
+            }
+        }
+        push_word(&chars[start..], &mut words);
+    }
+    words
+}
+
+fn push_word(chars: &[char], words: &mut Vec<String>) {
+    // Números soltos e letras isoladas não distinguem símbolos
+    if chars.len() > 1 && chars.iter().any(|c| c.is_alphabetic()) {
+        words.push(chars.iter().collect::<String>().to_lowercase());
+    }
+}
+
+fn stem(word: &str) -> String {
+    let word = word
+        .strip_suffix('e')
+        .filter(|stem| stem.len() >= MIN_STEM_LEN)
+        .unwrap_or(word);
+    SUFFIXES
+        .iter()
+        .find_map(|suffix| {
+            word.strip_suffix(suffix)
+                .filter(|stem| stem.len() >= MIN_STEM_LEN)
+        })
+        .unwrap_or(word)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_split_into_words() {
+        assert_eq!(terms("createUser"), ["creat", "user"]);
+        assert_eq!(terms("create_user"), ["creat", "user"]);
+        assert_eq!(terms("HTTPServer"), ["http", "serv"]);
+        assert_eq!(terms("userId"), ["user", "id"]);
+    }
+
+    #[test]
+    fn word_variants_share_a_stem() {
+        let stems: Vec<String> = ["creation", "created", "create", "creates", "creating"]
+            .iter()
+            .map(|word| stem(word))
+            .collect();
+        assert!(stems.iter().all(|stem| stem == "creat"), "{:?}", stems);
+        assert_eq!(stem("users"), "user");
+        // Radicais demasiado curtos ficam como estão
+        assert_eq!(stem("user"), "user");
+        assert_eq!(stem("bus"), "bus");
+    }
+
+    #[test]
+    fn questions_drop_stop_words() {
+        assert_eq!(
+            query_terms("How does user creation work?"),
+            ["user", "creat"]
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::language_config::LanguageConfig;
+use crate::relevant_context::symbol_contexts;
+use crate::search_terms::query_terms;
+use crate::symbol_index::{SymbolIndex, fnv1a};
+use crate::{Symbol, format_symbol};
+
+/// Muda sempre que o formato do ficheiro ou a forma dos vetores mudam
+const SEMANTIC_FORMAT: u32 = 1;
+
+/// Número de posições dos vetores; cada termo ou trigrama cai numa delas pelo hash
+const DIMENSIONS: u64 = 1 << 18;
+
+/// Peso de cada trigrama de um termo em relação ao termo inteiro. Os trigramas
+/// aproximam nomes escritos de formas diferentes (`usr`/`user`, `remove`/`rm_item`).
+const TRIGRAM_WEIGHT: f64 = 0.3;
+
+/// Vetor esparso: posições ordenadas e o respetivo peso
+type SparseVector = Vec<(u32, f32)>;
+
+/// Símbolo e o seu vetor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SymbolVector {
+    symbol: Symbol,
+    enclosing: Option<String>,
+    vector: SparseVector,
+}
+
+/// Vetores dos símbolos de um ficheiro e o hash do conteúdo de onde vieram
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileVectors {
+    hash: u64,
+    symbols: Vec<SymbolVector>,
+}
+
+/// Conteúdo do ficheiro do índice semântico
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SemanticFile {
+    format: u32,
+    /// Impressão digital das queries e gramáticas, como no índice de símbolos
+    config: u64,
+    files: HashMap<PathBuf, FileVectors>,
+}
+
+/// Símbolo encontrado por `search` e a semelhança com a pesquisa (0 a 1)
+pub(crate) struct SemanticMatch<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) symbol: &'a Symbol,
+    pub(crate) enclosing: Option<&'a str>,
+    pub(crate) similarity: f64,
+}
+
+/// Índice opcional de vetores dos símbolos, calculados localmente por hashing de
+/// termos e trigramas, e guardado ao lado do índice de símbolos. Só é criado quando
+/// é feita uma pesquisa semântica.
+pub(crate) struct SemanticIndex {
+    cache_path: Option<PathBuf>,
+    data: SemanticFile,
+    dirty: bool,
+    /// Ficheiros cujos vetores foram calculados na última atualização
+    updated: usize,
+}
+
+impl SemanticIndex {
+    pub(crate) fn open(index: &SymbolIndex, config: &LanguageConfig) -> Self {
+        let cache_path = index
+            .cache_path()
+            .map(|path| path.with_extension("semantic.json"));
+        let config_fingerprint = config.fingerprint();
+
+        let data = cache_path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<SemanticFile>(&bytes).ok())
+            .filter(|data| data.format == SEMANTIC_FORMAT && data.config == config_fingerprint)
+            .unwrap_or_else(|| SemanticFile {
+                format: SEMANTIC_FORMAT,
+                config: config_fingerprint,
+                files: HashMap::new(),
+            });
+
+        SemanticIndex {
+            cache_path,
+            data,
+            dirty: false,
+            updated: 0,
+        }
+    }
+
+    /// Calcula os vetores dos ficheiros novos ou alterados, esquece os que já não
+    /// estão na lista e guarda o índice se algo mudou
+    pub(crate) fn update(&mut self, workspace_dir: &Path, files: &[PathBuf], index: &SymbolIndex) {
+        self.updated = 0;
+        let mut current = HashMap::new();
+
+        for file in files {
+            let Some(hash) = index.content_hash(file) else {
+                continue;
+            };
+            let relative = file.strip_prefix(workspace_dir).unwrap_or(file);
+            if let Some(vectors) = self
+                .data
+                .files
+                .remove(relative)
+                .filter(|vectors| vectors.hash == hash)
+            {
+                current.insert(relative.to_path_buf(), vectors);
+                continue;
+            }
+
+            let symbols = symbol_contexts(workspace_dir, std::slice::from_ref(file), index)
+                .into_iter()
+                .map(|context| SymbolVector {
+                    vector: vectorize(&context.weighted_terms()),
+                    symbol: context.symbol,
+                    enclosing: context.enclosing,
+                })
+                .collect();
+            current.insert(relative.to_path_buf(), FileVectors { hash, symbols });
+            self.updated += 1;
+        }
+
+        self.dirty |= self.updated > 0 || !self.data.files.is_empty();
+        self.data.files = current;
+        if self.dirty {
+            self.save();
+        }
+    }
+
+    /// Os `k` símbolos mais próximos da pesquisa, por semelhança de cosseno com os
+    /// pesos TF-IDF do workspace
+    pub(crate) fn search(&self, query: &str, k: usize) -> Vec<SemanticMatch<'_>> {
+        let mut query_weights = HashMap::new();
+        for term in query_terms(query) {
+            *query_weights.entry(term).or_insert(0.0) += 1.0;
+        }
+        let query_vector = vectorize(&query_weights);
+        if query_vector.is_empty() {
+            return Vec::new();
+        }
+
+        // Frequência de cada posição nos documentos, para o IDF
+        let entries: Vec<(&PathBuf, &SymbolVector)> = self
+            .data
+            .files
+            .iter()
+            .flat_map(|(path, vectors)| vectors.symbols.iter().map(move |symbol| (path, symbol)))
+            .collect();
+        let mut frequencies: HashMap<u32, usize> = HashMap::new();
+        for (_, entry) in &entries {
+            for (position, _) in &entry.vector {
+                *frequencies.entry(*position).or_insert(0) += 1;
+            }
+        }
+        let count = entries.len() as f64;
+        let idf = |position: &u32| {
+            let frequency = frequencies.get(position).copied().unwrap_or(0) as f64;
+            ((count + 1.0) / (frequency + 1.0)).ln() + 1.0
+        };
+
+        let query_weighted: HashMap<u32, f64> = query_vector
+            .iter()
+            .map(|(position, weight)| (*position, f64::from(*weight) * idf(position)))
+            .collect();
+        let query_norm = norm(query_weighted.values().copied());
+
+        let mut matches: Vec<SemanticMatch> = entries
+            .iter()
+            .filter_map(|(path, entry)| {
+                let weighted = entry
+                    .vector
+                    .iter()
+                    .map(|(position, weight)| (position, f64::from(*weight) * idf(position)));
+                let mut dot = 0.0;
+                let mut squares = 0.0;
+                for (position, weight) in weighted {
+                    squares += weight * weight;
+                    if let Some(query_weight) = query_weighted.get(position) {
+                        dot += weight * query_weight;
+                    }
+                }
+                (dot > 0.0).then(|| SemanticMatch {
+                    path,
+                    symbol: &entry.symbol,
+                    enclosing: entry.enclosing.as_deref(),
+                    similarity: dot / (squares.sqrt() * query_norm),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.similarity
+                .total_cmp(&a.similarity)
+                .then_with(|| a.path.cmp(b.path))
+                .then_with(|| a.symbol.start_line.cmp(&b.symbol.start_line))
+        });
+        matches.truncate(k);
+        matches
+    }
+
+    /// Número de símbolos no índice
+    pub(crate) fn symbol_count(&self) -> usize {
+        self.data
+            .files
+            .values()
+            .map(|file| file.symbols.len())
+            .sum()
+    }
+
+    /// Escreve num ficheiro temporário e substitui o anterior, como o índice de símbolos
+    fn save(&mut self) {
+        let Some(cache_path) = &self.cache_path else {
+            return;
+        };
+        let result = (|| -> anyhow::Result<()> {
+            if let Some(dir) = cache_path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let temporary = cache_path.with_extension("tmp");
+            fs::write(&temporary, serde_json::to_vec(&self.data)?)?;
+            fs::rename(&temporary, cache_path)?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(error) => eprintln!(
+                "⚠️  Cannot write semantic index {}: {}",
+                cache_path.display(),
+                error
+            ),
+        }
+    }
+}
+
+/// Vetor de termos com peso: cada termo e os seus trigramas (`^us`, `use`, `ser`, `er$`)
+/// somam o peso, atenuado logaritmicamente, na posição dada pelo seu hash
+fn vectorize(weighted_terms: &HashMap<String, f64>) -> SparseVector {
+    let mut positions: HashMap<u32, f64> = HashMap::new();
+    for (term, weight) in weighted_terms {
+        let weight = (1.0 + weight).ln();
+        *positions.entry(position(term)).or_insert(0.0) += weight;
+
+        let padded: Vec<char> = format!("^{}$", term).chars().collect();
+        for trigram in padded.windows(3) {
+            let trigram: String = trigram.iter().collect();
+            *positions
+                .entry(position(&format!("#{}", trigram)))
+                .or_insert(0.0) += weight * TRIGRAM_WEIGHT;
+        }
+    }
+
+    let mut vector: SparseVector = positions
+        .into_iter()
+        .map(|(position, weight)| (position, weight as f32))
+        .collect();
+    vector.sort_by_key(|(position, _)| *position);
+    vector
+}
+
+fn position(feature: &str) -> u32 {
+    (fnv1a(feature.as_bytes()) % DIMENSIONS) as u32
+}
+
+fn norm(weights: impl Iterator<Item = f64>) -> f64 {
+    weights.map(|weight| weight * weight).sum::<f64>().sqrt()
+}
+
+/// Símbolos mais próximos da pesquisa, com a semelhança e a localização
+pub(crate) fn format_semantic_search(
+    semantic: &SemanticIndex,
+    query: &str,
+    k: usize,
+    truncated: Option<&str>,
+) -> String {
+    let mut result = String::new();
+    result.push_str("🧭 Semantic Search\n");
+    result.push_str("══════════════════════════════════\n");
+    result.push_str(&format!("❓ {}\n\n", query.trim()));
+
+    let matches = semantic.search(query, k);
+    if matches.is_empty() {
+        result.push_str("No similar symbols found.\n");
+    }
+    for (rank, found) in matches.iter().enumerate() {
+        let enclosing = found
+            .enclosing
+            .map(|enclosing| format!(" in {}", enclosing))
+            .unwrap_or_default();
+        result.push_str(&format!(
+            "{:>2}. {:.2}  {}:{}  {}{}\n",
+            rank + 1,
+            found.similarity,
+            found.path.display(),
+            found.symbol.start_line,
+            format_symbol(&found.symbol.to_string()),
+            enclosing
+        ));
+    }
+
+    result.push_str(&format!(
+        "\n📊 Summary:\n\
+         • {} symbols in the semantic index ({} files updated)\n\
+         • Terms: {}\n",
+        semantic.symbol_count(),
+        semantic.updated,
+        query_terms(query).join(", ")
+    ));
+    if let Some(reason) = truncated {
+        result.push_str(&format!("• Truncated due to time budget: {}\n", reason));
+    }
+    result
+}
@@ -0,0 +1,676 @@
+use anyhow::Result;
+use std::path::Path;
+use tree_sitter::Node;
+
+use crate::language_config::LanguageConfig;
+use crate::{Symbol, extract_symbols_from_source, language_for_path, parse_source};
+
+/// Bloco de topo de um componente (`<script>`, `<template>`, `<style>`)
+struct Block {
+    tag: String,
+    attributes: String,
+    /// Intervalo de bytes do conteúdo entre as tags de abertura e de fecho
+    content_start: usize,
+    content_end: usize,
+}
+
+impl Block {
+    fn has_attribute(&self, name: &str) -> bool {
+        self.attributes
+            .split(|c: char| c.is_whitespace() || c == '=')
+            .any(|word| word == name)
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        let start = self.attributes.find(&format!("{}=", name))? + name.len() + 1;
+        let value = &self.attributes[start..];
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        value[1..].split(quote).next()
+    }
+
+    /// Extensão equivalente do bloco `<script>` para escolher a gramática
+    fn script_extension(&self) -> &'static str {
+        match self.attribute("lang") {
+            Some("ts") | Some("typescript") => "ts",
+            Some("tsx") => "tsx",
+            Some("jsx") => "jsx",
+            _ => "js",
+        }
+    }
+
+    /// Descrição curta do bloco: `script setup (ts)`, `style scoped`, ...
+    fn describe(&self) -> String {
+        let mut description = self.tag.clone();
+        if self.tag == "script" {
+            if self.has_attribute("setup") {
+                description.push_str(" setup");
+            }
+            if self.has_attribute("module") || self.attribute("context") == Some("module") {
+                description.push_str(" module");
+            }
+            if let Some(lang) = self.attribute("lang") {
+                description.push_str(&format!(" ({})", lang));
+            }
+        } else if self.tag == "style" && self.has_attribute("scoped") {
+            description.push_str(" scoped");
+        }
+        description
+    }
+}
+
+/// API pública do componente declarada nos blocos `<script>`
+#[derive(Default)]
+struct ComponentApi {
+    props: Vec<Symbol>,
+    emits: Vec<Symbol>,
+    functions: Vec<Symbol>,
+    /// `const props = defineProps(...)`: variáveis que só repetem a API já listada
+    bindings: Vec<Symbol>,
+}
+
+/// Verifica se o ficheiro é um componente Vue ou Svelte
+pub(crate) fn is_sfc_path(file_path: &Path) -> bool {
+    matches!(
+        file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .as_deref(),
+        Some("vue") | Some("svelte")
+    )
+}
+
+/// Divide o componente nos seus blocos e extrai os símbolos dos `<script>` com a
+/// extração JS/TS habitual. Props, eventos e funções exportadas ficam aninhados num
+/// símbolo `component` com o nome do ficheiro.
+pub(crate) fn extract_sfc_symbols(
+    file_path: &Path,
+    content: &str,
+    config: &LanguageConfig,
+) -> Result<Vec<Symbol>> {
+    let blocks = split_blocks(content);
+    let is_svelte = file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svelte"));
+    let component_name = file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut api = ComponentApi::default();
+    let mut script_symbols = Vec::new();
+
+    for block in blocks.iter().filter(|block| block.tag == "script") {
+        let script = &content[block.content_start..block.content_end];
+        let script_path = Path::new("script").with_extension(block.script_extension());
+        let line_offset = content[..block.content_start].matches('\n').count();
+
+        let mut symbols = extract_symbols_from_source(&script_path, script, config)?;
+        let mut block_api = ComponentApi::default();
+        collect_component_api(&script_path, script, &component_name, &mut block_api)?;
+
+        for symbol in symbols
+            .iter_mut()
+            .chain(&mut block_api.props)
+            .chain(&mut block_api.emits)
+            .chain(&mut block_api.functions)
+            .chain(&mut block_api.bindings)
+        {
+            symbol.start_line += line_offset;
+            symbol.end_line += line_offset;
+            symbol.start_byte += block.content_start;
+            symbol.end_byte += block.content_start;
+        }
+
+        script_symbols.extend(symbols);
+        api.props.extend(block_api.props);
+        api.emits.extend(block_api.emits);
+        api.functions.extend(block_api.functions);
+        api.bindings.extend(block_api.bindings);
+    }
+
+    let mut block_names: Vec<String> = blocks.iter().map(Block::describe).collect();
+    // Em Svelte o markup fica fora de qualquer bloco
+    if is_svelte && !content_outside_blocks(content, &blocks).trim().is_empty() {
+        block_names.push("markup".to_string());
+    }
+
+    let mut result = vec![Symbol {
+        kind: "component".to_string(),
+        name: component_name,
+        start_line: 1,
+        end_line: content.lines().count().max(1),
+        start_byte: 0,
+        end_byte: content.len(),
+        detail: (!block_names.is_empty()).then(|| block_names.join(", ")),
+        container: None,
+    }];
+
+    // Props (`export let`) e funções exportadas já aparecem aninhadas no componente
+    let api_symbols: Vec<Symbol> = api
+        .props
+        .into_iter()
+        .chain(api.emits)
+        .chain(api.functions)
+        .collect();
+    let is_nested = |symbol: &Symbol| {
+        api_symbols.iter().chain(&api.bindings).any(|api_symbol| {
+            api_symbol.name == symbol.name
+                && api_symbol.start_byte <= symbol.start_byte
+                && symbol.end_byte <= api_symbol.end_byte
+        })
+    };
+    script_symbols.retain(|symbol| !is_nested(symbol));
+
+    result.extend(api_symbols);
+    result.extend(script_symbols);
+
+    Ok(result)
+}
+
+/// Blocos de topo do componente, ignorando comentários HTML.
+/// `<template>` pode conter outros `<template>`, por isso o fecho é emparelhado.
+fn split_blocks(content: &str) -> Vec<Block> {
+    let lower = content.to_ascii_lowercase();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = lower[pos..].find('<') {
+        let start = pos + offset;
+        if lower[start..].starts_with("<!--") {
+            pos = lower[start..]
+                .find("-->")
+                .map_or(lower.len(), |end| start + end + 3);
+            continue;
+        }
+
+        let Some(tag) = ["script", "template", "style"].into_iter().find(|tag| {
+            lower[start + 1..].starts_with(tag)
+                && lower[start + 1 + tag.len()..]
+                    .starts_with(|c: char| c == '>' || c.is_whitespace())
+        }) else {
+            pos = start + 1;
+            continue;
+        };
+
+        let Some(open_end) = lower[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+        let attributes = content[start + 1 + tag.len()..open_end]
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        if lower[..open_end].ends_with('/') {
+            pos = open_end + 1;
+            continue;
+        }
+
+        let content_start = open_end + 1;
+        let content_end = find_closing_tag(&lower, tag, content_start).unwrap_or(lower.len());
+        blocks.push(Block {
+            tag: tag.to_string(),
+            attributes,
+            content_start,
+            content_end,
+        });
+
+        pos = lower[content_end..]
+            .find('>')
+            .map_or(lower.len(), |end| content_end + end + 1);
+    }
+
+    blocks
+}
+
+/// Posição de `</tag>` correspondente, contando aberturas aninhadas da mesma tag
+fn find_closing_tag(lower: &str, tag: &str, from: usize) -> Option<usize> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}", tag);
+    let mut depth = 1;
+    let mut pos = from;
+
+    loop {
+        let next_close = lower[pos..].find(&close)? + pos;
+        // Apenas `<template>` pode aninhar-se; scripts e estilos fecham no primeiro `</tag>`
+        let next_open = (tag == "template")
+            .then(|| {
+                lower[pos..next_close]
+                    .find(&open)
+                    .map(|offset| offset + pos)
+            })
+            .flatten();
+
+        match next_open {
+            Some(open_pos) => {
+                depth += 1;
+                pos = open_pos + open.len();
+            }
+            None => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(next_close);
+                }
+                pos = next_close + close.len();
+            }
+        }
+    }
+}
+
+/// Texto fora dos blocos de topo (o markup de um componente Svelte)
+fn content_outside_blocks(content: &str, blocks: &[Block]) -> String {
+    let mut outside = String::new();
+    let mut pos = 0;
+    for block in blocks {
+        let tag_start = content[..block.content_start]
+            .rfind('<')
+            .unwrap_or(block.content_start);
+        outside.push_str(&content[pos.min(tag_start)..tag_start]);
+        pos = content[block.content_end..]
+            .find('>')
+            .map_or(content.len(), |end| block.content_end + end + 1);
+    }
+    outside.push_str(&content[pos.min(content.len())..]);
+    outside
+}
+
+/// Percorre a árvore do `<script>` à procura de props, eventos e funções exportadas
+fn collect_component_api(
+    script_path: &Path,
+    script: &str,
+    component: &str,
+    api: &mut ComponentApi,
+) -> Result<()> {
+    let Some((language, _, _)) = language_for_path(script_path) else {
+        return Ok(());
+    };
+    let Some(tree) = parse_source(language, script, None, None)? else {
+        return Ok(());
+    };
+
+    let mut collector = ApiCollector {
+        source: script,
+        root: tree.root_node(),
+        component,
+        api,
+    };
+    collector.visit(tree.root_node());
+    Ok(())
+}
+
+struct ApiCollector<'a> {
+    source: &'a str,
+    root: Node<'a>,
+    component: &'a str,
+    api: &'a mut ComponentApi,
+}
+
+impl<'a> ApiCollector<'a> {
+    fn text(&self, node: Node) -> &str {
+        node.utf8_text(self.source.as_bytes()).unwrap_or_default()
+    }
+
+    fn symbol(&self, kind: &str, name: &str, node: Node, detail: Option<String>) -> Symbol {
+        Symbol {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            start_line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            detail,
+            container: Some(self.component.to_string()),
+        }
+    }
+
+    fn visit(&mut self, node: Node) {
+        match node.kind() {
+            "call_expression" => self.visit_call(node),
+            "export_statement" => self.visit_export(node),
+            "variable_declarator" => self.visit_declarator(node),
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.visit(child);
+        }
+    }
+
+    fn visit_call(&mut self, call: Node) {
+        let Some(function) = call.child_by_field_name("function") else {
+            return;
+        };
+        let type_arguments = call
+            .child_by_field_name("type_arguments")
+            .and_then(|arguments| arguments.named_child(0));
+        let first_argument = call
+            .child_by_field_name("arguments")
+            .and_then(|arguments| arguments.named_child(0));
+
+        match self.text(function) {
+            // Vue `<script setup>`: defineProps<{...}>() / defineProps({...}) / defineProps([...])
+            "defineProps" => {
+                if let Some(props_type) = type_arguments {
+                    let props_type = self.resolve_type(props_type);
+                    self.props_from_type(props_type);
+                }
+                if let Some(argument) = first_argument {
+                    self.props_from_value(argument);
+                }
+            }
+            // Vue: defineEmits<{...}>() / defineEmits([...]); Svelte: createEventDispatcher<{...}>()
+            "defineEmits" | "createEventDispatcher" => {
+                if let Some(emits_type) = type_arguments {
+                    let emits_type = self.resolve_type(emits_type);
+                    self.emits_from_type(emits_type);
+                }
+                if let Some(argument) = first_argument {
+                    self.emits_from_value(argument);
+                }
+            }
+            // Vue (options API): defineComponent({ props, emits })
+            "defineComponent" => {
+                if let Some(options) = first_argument.filter(|node| node.kind() == "object") {
+                    self.visit_options(options);
+                }
+            }
+            // Svelte: dispatch('evento', ...)
+            "dispatch" => {
+                if let Some(name) = first_argument.filter(|node| node.kind() == "string") {
+                    let name = unquote(self.text(name)).to_string();
+                    self.push_emit(&name, call);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_export(&mut self, export: Node) {
+        // Vue (options API): export default { props: ..., emits: ... }
+        if let Some(options) = export
+            .child_by_field_name("value")
+            .filter(|node| node.kind() == "object")
+        {
+            self.visit_options(options);
+            return;
+        }
+
+        let Some(declaration) = export.child_by_field_name("declaration") else {
+            return;
+        };
+        match declaration.kind() {
+            "function_declaration" | "generator_function_declaration" => {
+                if let Some(name) = declaration.child_by_field_name("name") {
+                    let name = self.text(name).to_string();
+                    let function = self.symbol("fn", &name, export, None);
+                    self.api.functions.push(function);
+                }
+            }
+            "lexical_declaration" | "variable_declaration" => {
+                // Svelte: `export let nome` é uma prop; `export const f = () => ...` uma função
+                let is_let = self.text(declaration).starts_with("let");
+                let mut cursor = declaration.walk();
+                for declarator in declaration.named_children(&mut cursor) {
+                    let Some(name) = declarator.child_by_field_name("name") else {
+                        continue;
+                    };
+                    let name = self.text(name).to_string();
+                    let is_function =
+                        declarator
+                            .child_by_field_name("value")
+                            .is_some_and(|value| {
+                                matches!(
+                                    value.kind(),
+                                    "arrow_function" | "function" | "function_expression"
+                                )
+                            });
+                    if is_function {
+                        let function = self.symbol("fn", &name, export, None);
+                        self.api.functions.push(function);
+                    } else if is_let {
+                        let detail = declarator
+                            .child_by_field_name("type")
+                            .map(|annotation| type_text(self.text(annotation)));
+                        let prop = self.symbol("prop", &name, declarator, detail);
+                        self.api.props.push(prop);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Svelte 5: `let { nome, outro = 1 } = $props()`. Em Vue, `const props =
+    /// defineProps(...)` é guardado para não repetir a API como variável.
+    fn visit_declarator(&mut self, declarator: Node) {
+        let callee = declarator
+            .child_by_field_name("value")
+            .and_then(|value| value.child_by_field_name("function"))
+            .map(|function| self.text(function));
+        if matches!(callee, Some("defineProps" | "defineEmits" | "withDefaults"))
+            && let Some(name) = declarator.child_by_field_name("name")
+        {
+            let name = self.text(name).to_string();
+            let declaration = declarator.parent().unwrap_or(declarator);
+            let binding = self.symbol("binding", &name, declaration, None);
+            self.api.bindings.push(binding);
+            return;
+        }
+
+        let is_props_rune = callee == Some("$props");
+        let Some(pattern) = declarator
+            .child_by_field_name("name")
+            .filter(|pattern| is_props_rune && pattern.kind() == "object_pattern")
+        else {
+            return;
+        };
+
+        let mut cursor = pattern.walk();
+        for child in pattern.named_children(&mut cursor) {
+            let name = match child.kind() {
+                "shorthand_property_identifier_pattern" => Some(child),
+                "object_assignment_pattern" => child.child_by_field_name("left"),
+                "pair_pattern" => child.child_by_field_name("key"),
+                _ => None,
+            };
+            if let Some(name) = name {
+                let name = self.text(name).to_string();
+                let prop = self.symbol("prop", &name, child, None);
+                self.api.props.push(prop);
+            }
+        }
+    }
+
+    /// Opções de um componente Vue (`{ props: ..., emits: ... }`)
+    fn visit_options(&mut self, options: Node) {
+        let mut cursor = options.walk();
+        for pair in options
+            .named_children(&mut cursor)
+            .filter(|child| child.kind() == "pair")
+        {
+            let (Some(key), Some(value)) = (
+                pair.child_by_field_name("key"),
+                pair.child_by_field_name("value"),
+            ) else {
+                continue;
+            };
+            match unquote(self.text(key)) {
+                "props" => self.props_from_value(value),
+                "emits" => self.emits_from_value(value),
+                _ => {}
+            }
+        }
+    }
+
+    /// `{ nome: String, outro: { type: Number } }` ou `['nome', 'outro']`
+    fn props_from_value(&mut self, value: Node) {
+        let mut cursor = value.walk();
+        for child in value.named_children(&mut cursor) {
+            let prop = match child.kind() {
+                "string" => {
+                    let name = unquote(self.text(child)).to_string();
+                    self.symbol("prop", &name, child, None)
+                }
+                "shorthand_property_identifier" => {
+                    let name = self.text(child).to_string();
+                    self.symbol("prop", &name, child, None)
+                }
+                "pair" => {
+                    let (Some(key), Some(prop_value)) = (
+                        child.child_by_field_name("key"),
+                        child.child_by_field_name("value"),
+                    ) else {
+                        continue;
+                    };
+                    let name = unquote(self.text(key)).to_string();
+                    let detail = self.prop_type(prop_value);
+                    self.symbol("prop", &name, child, detail)
+                }
+                _ => continue,
+            };
+            self.api.props.push(prop);
+        }
+    }
+
+    /// Tipo de uma prop nas opções: `String` ou `{ type: String, ... }`
+    fn prop_type(&self, value: Node) -> Option<String> {
+        match value.kind() {
+            "identifier" | "array" => Some(self.text(value).to_string()),
+            "object" => {
+                let mut cursor = value.walk();
+                value
+                    .named_children(&mut cursor)
+                    .filter(|child| child.kind() == "pair")
+                    .find(|pair| {
+                        pair.child_by_field_name("key")
+                            .is_some_and(|key| self.text(key) == "type")
+                    })
+                    .and_then(|pair| pair.child_by_field_name("value"))
+                    .map(|types| self.text(types).to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Corpo da interface ou do alias declarado no mesmo script para `defineProps<Props>()`;
+    /// outros tipos (importados, genéricos) são devolvidos tal como estão
+    fn resolve_type<'t>(&self, type_node: Node<'t>) -> Node<'t>
+    where
+        'a: 't,
+    {
+        if type_node.kind() != "type_identifier" {
+            return type_node;
+        }
+        let name = self.text(type_node);
+
+        let mut cursor = self.root.walk();
+        let statements: Vec<Node> = self.root.named_children(&mut cursor).collect();
+        statements
+            .into_iter()
+            .map(|statement| {
+                statement
+                    .child_by_field_name("declaration")
+                    .filter(|_| statement.kind() == "export_statement")
+                    .unwrap_or(statement)
+            })
+            .find_map(|declaration| {
+                let body = match declaration.kind() {
+                    "interface_declaration" => declaration.child_by_field_name("body"),
+                    "type_alias_declaration" => declaration.child_by_field_name("value"),
+                    _ => None,
+                };
+                declaration
+                    .child_by_field_name("name")
+                    .filter(|declared| self.text(*declared) == name)
+                    .and(body)
+            })
+            .unwrap_or(type_node)
+    }
+
+    /// `defineProps<{ nome: string; outro?: number }>()`
+    fn props_from_type(&mut self, props_type: Node) {
+        let mut cursor = props_type.walk();
+        for member in props_type
+            .named_children(&mut cursor)
+            .filter(|member| member.kind() == "property_signature")
+        {
+            let Some(name) = member.child_by_field_name("name") else {
+                continue;
+            };
+            let name = self.text(name).to_string();
+            let detail = member
+                .child_by_field_name("type")
+                .map(|annotation| type_text(self.text(annotation)));
+            let prop = self.symbol("prop", &name, member, detail);
+            self.api.props.push(prop);
+        }
+    }
+
+    /// `['change', 'update:modelValue']` ou `{ change: (id) => true }`
+    fn emits_from_value(&mut self, value: Node) {
+        let mut cursor = value.walk();
+        for child in value.named_children(&mut cursor) {
+            let name = match child.kind() {
+                "string" => unquote(self.text(child)).to_string(),
+                "shorthand_property_identifier" => self.text(child).to_string(),
+                "pair" | "method_definition" => {
+                    let field = if child.kind() == "pair" {
+                        "key"
+                    } else {
+                        "name"
+                    };
+                    match child.child_by_field_name(field) {
+                        Some(key) => unquote(self.text(key)).to_string(),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            self.push_emit(&name, child);
+        }
+    }
+
+    /// `{ (e: 'change', id: number): void }` ou `{ change: [id: number] }`
+    fn emits_from_type(&mut self, emits_type: Node) {
+        let mut cursor = emits_type.walk();
+        for member in emits_type.named_children(&mut cursor) {
+            let name = match member.kind() {
+                "property_signature" => member
+                    .child_by_field_name("name")
+                    .map(|name| unquote(self.text(name)).to_string()),
+                "call_signature" => member
+                    .child_by_field_name("parameters")
+                    .and_then(|parameters| parameters.named_child(0))
+                    .and_then(|parameter| parameter.child_by_field_name("type"))
+                    .map(|annotation| unquote(&type_text(self.text(annotation))).to_string()),
+                _ => None,
+            };
+            if let Some(name) = name {
+                self.push_emit(&name, member);
+            }
+        }
+    }
+
+    /// Regista um evento uma única vez, mesmo que seja emitido em vários sítios
+    fn push_emit(&mut self, name: &str, node: Node) {
+        if name.is_empty() || self.api.emits.iter().any(|emit| emit.name == name) {
+            return;
+        }
+        let emit = self.symbol("emit", name, node, None);
+        self.api.emits.push(emit);
+    }
+}
+
+/// Texto de uma anotação de tipo sem o `:` inicial
+fn type_text(annotation: &str) -> String {
+    annotation
+        .trim_start_matches(':')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Remove as aspas de uma string JS
+fn unquote(text: &str) -> &str {
+    text.trim_matches(|c| c == '"' || c == '\'' || c == '`')
+}
@@ -0,0 +1,1462 @@
+use anyhow::{Result, anyhow};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::{IGNORED_DIRS, Symbol};
+
+/// Nomes de diretorias que contêm migrações (Diesel, sqlx, Flyway, Rails, Knex, ...)
+const MIGRATION_DIRS: [&str; 3] = ["migrations", "migration", "migrate"];
+
+/// Palavras que terminam o tipo de uma coluna e iniciam as suas restrições
+const COLUMN_CONSTRAINT_KEYWORDS: [&str; 15] = [
+    "NOT",
+    "NULL",
+    "DEFAULT",
+    "PRIMARY",
+    "REFERENCES",
+    "UNIQUE",
+    "CHECK",
+    "CONSTRAINT",
+    "GENERATED",
+    "COLLATE",
+    "AUTO_INCREMENT",
+    "AUTOINCREMENT",
+    "IDENTITY",
+    "COMMENT",
+    "ON",
+];
+
+/// Tipo de token SQL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// Palavra-chave ou identificador sem aspas
+    Word,
+    /// Identificador entre aspas (`"x"`, `` `x` ``, `[x]`)
+    Quoted,
+    /// String ou corpo `$$ ... $$`
+    Literal,
+    Punct,
+}
+
+/// Token com o texto original e a sua posição no ficheiro
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+    line: usize,
+    end_line: usize,
+}
+
+impl Token<'_> {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct && self.text.starts_with(c)
+    }
+
+    /// Nome do identificador, sem aspas
+    fn ident(&self) -> Option<String> {
+        match self.kind {
+            TokenKind::Word => Some(self.text.to_string()),
+            TokenKind::Quoted => {
+                // Um identificador sem aspa de fecho vai até ao fim do ficheiro
+                let mut chars = self.text.chars();
+                let close = match chars.next()? {
+                    '[' => ']',
+                    open => open,
+                };
+                let inner = chars.as_str();
+                Some(inner.strip_suffix(close).unwrap_or(inner).to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Localização de uma instrução ou coluna no ficheiro
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start_line: usize,
+    end_line: usize,
+    start_byte: usize,
+    end_byte: usize,
+}
+
+impl Span {
+    fn of(tokens: &[Token]) -> Self {
+        let (first, last) = (&tokens[0], &tokens[tokens.len() - 1]);
+        Span {
+            start_line: first.line,
+            end_line: last.end_line,
+            start_byte: first.start,
+            end_byte: last.end,
+        }
+    }
+
+    fn symbol(
+        &self,
+        kind: &str,
+        name: &str,
+        detail: Option<String>,
+        container: Option<String>,
+    ) -> Symbol {
+        Symbol {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            start_line: self.start_line,
+            end_line: self.end_line,
+            start_byte: self.start_byte,
+            end_byte: self.end_byte,
+            detail,
+            container,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Column {
+    name: String,
+    data_type: String,
+    primary_key: bool,
+    not_null: bool,
+    unique: bool,
+    references: Option<String>,
+    span: Span,
+}
+
+#[derive(Debug, Clone)]
+struct Table {
+    name: String,
+    columns: Vec<Column>,
+}
+
+#[derive(Debug, Clone)]
+struct Index {
+    name: Option<String>,
+    table: String,
+    columns: String,
+    unique: bool,
+}
+
+#[derive(Debug, Clone)]
+struct View {
+    name: String,
+    materialized: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Function {
+    name: String,
+    arguments: String,
+    procedure: bool,
+}
+
+/// Restrição declarada ao nível da tabela (`PRIMARY KEY (a, b)`, `FOREIGN KEY ...`)
+#[derive(Debug, Clone)]
+enum TableConstraint {
+    PrimaryKey(Vec<String>),
+    Unique(Vec<String>),
+    ForeignKey { columns: Vec<String>, table: String },
+}
+
+#[derive(Debug, Clone)]
+enum AlterAction {
+    AddColumn(Column),
+    AddConstraint(TableConstraint),
+    DropColumn(String),
+    RenameColumn { from: String, to: String },
+    RenameTable(String),
+    SetType { column: String, data_type: String },
+    SetNotNull { column: String, not_null: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectKind {
+    Table,
+    Index,
+    View,
+    Function,
+}
+
+/// Instrução DDL reconhecida; as restantes são ignoradas
+#[derive(Debug, Clone)]
+enum Statement {
+    CreateTable(Table),
+    CreateIndex(Index),
+    CreateView(View),
+    CreateFunction(Function),
+    AlterTable {
+        name: String,
+        actions: Vec<AlterAction>,
+    },
+    Drop {
+        object: ObjectKind,
+        names: Vec<String>,
+    },
+}
+
+/// Esquema resultante de aplicar as migrações por ordem
+#[derive(Debug, Default)]
+struct Schema {
+    tables: Vec<Table>,
+    indexes: Vec<Index>,
+    views: Vec<View>,
+    functions: Vec<Function>,
+}
+
+/// Verifica se o ficheiro é SQL pela extensão
+pub(crate) fn is_sql_path(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("sql"))
+}
+
+/// Extrai tabelas (com as colunas aninhadas), índices, vistas e funções
+/// criadas num ficheiro SQL, pela ordem do documento
+pub(crate) fn extract_sql_symbols(content: &str) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+
+    for (statement, span) in parse_statements(content) {
+        match statement {
+            Statement::CreateTable(table) => {
+                symbols.push(span.symbol("table", &table.name, None, None));
+                for column in &table.columns {
+                    symbols.push(column.span.symbol(
+                        "column",
+                        &column.name,
+                        Some(column.data_type.clone()).filter(|t| !t.is_empty()),
+                        Some(table.name.clone()),
+                    ));
+                }
+            }
+            Statement::CreateIndex(index) => {
+                let name = index.name.clone().unwrap_or_else(|| index.table.clone());
+                symbols.push(span.symbol("index", &name, Some(index_detail(&index)), None));
+            }
+            Statement::CreateView(view) => {
+                let detail = view.materialized.then(|| "materialized".to_string());
+                symbols.push(span.symbol("view", &view.name, detail, None));
+            }
+            Statement::CreateFunction(function) => {
+                let detail = function.procedure.then(|| "procedure".to_string());
+                symbols.push(span.symbol("fn", &function.name, detail, None));
+            }
+            Statement::AlterTable { .. } | Statement::Drop { .. } => {}
+        }
+    }
+
+    symbols
+}
+
+/// Aplica os ficheiros SQL das migrações por ordem e retorna o esquema final:
+/// tabelas com colunas e tipos, índices, vistas e funções
+pub(crate) fn format_database_schema(
+    workspace_dir: &Path,
+    migrations_path: Option<&str>,
+) -> Result<String> {
+    let (root, files) = match migrations_path {
+        Some(migrations_path) => {
+            let root = workspace_dir.join(migrations_path);
+            if !root.exists() {
+                return Err(anyhow!("Migrations path not found: {}", root.display()));
+            }
+            let files = collect_sql_files(&root);
+            (root, files)
+        }
+        None => {
+            let all_files = collect_sql_files(workspace_dir);
+            let migrations: Vec<PathBuf> = all_files
+                .iter()
+                .filter(|path| is_in_migrations_dir(workspace_dir, path))
+                .cloned()
+                .collect();
+            // Sem diretoria de migrações, usar os ficheiros .sql soltos (schema.sql, ...)
+            let files = if migrations.is_empty() {
+                all_files
+            } else {
+                migrations
+            };
+            (workspace_dir.to_path_buf(), files)
+        }
+    };
+
+    let mut schema = Schema::default();
+    for file in &files {
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+        for (statement, _) in parse_statements(&content) {
+            schema.apply(statement);
+        }
+    }
+
+    let mut result = String::new();
+    result.push_str("🗄️  Database Schema\n");
+    result.push_str("══════════════════════════════════\n\n");
+
+    if files.is_empty() {
+        result.push_str("No SQL files found.\n\n");
+    } else {
+        let relative = |path: &PathBuf| {
+            path.strip_prefix(&root)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        result.push_str(&format!("📜 Applied {} SQL files in order", files.len()));
+        if files.len() > 1 {
+            result.push_str(&format!(
+                " ({} → {})",
+                relative(&files[0]),
+                relative(&files[files.len() - 1])
+            ));
+        } else {
+            result.push_str(&format!(" ({})", relative(&files[0])));
+        }
+        result.push_str("\n\n");
+    }
+
+    for table in &schema.tables {
+        result.push_str(&format!("🔑 table {}\n", table.name));
+        for column in &table.columns {
+            result.push_str(&format!("  • {}\n", describe_column(column)));
+        }
+        for index in schema
+            .indexes
+            .iter()
+            .filter(|index| same_name(&index.table, &table.name))
+        {
+            match &index.name {
+                Some(name) => {
+                    result.push_str(&format!("  🔎 index {}: {}\n", name, index_detail(index)))
+                }
+                None => result.push_str(&format!("  🔎 index {}\n", index_detail(index))),
+            }
+        }
+        result.push('\n');
+    }
+
+    if !schema.views.is_empty() {
+        result.push_str("👁️  Views:\n");
+        for view in &schema.views {
+            let marker = if view.materialized {
+                " (materialized)"
+            } else {
+                ""
+            };
+            result.push_str(&format!("  • {}{}\n", view.name, marker));
+        }
+        result.push('\n');
+    }
+
+    if !schema.functions.is_empty() {
+        result.push_str("🔧 Functions:\n");
+        for function in &schema.functions {
+            let marker = if function.procedure {
+                " (procedure)"
+            } else {
+                ""
+            };
+            result.push_str(&format!(
+                "  • {}({}){}\n",
+                function.name, function.arguments, marker
+            ));
+        }
+        result.push('\n');
+    }
+
+    result.push_str(&format!(
+        "📊 Summary:\n\
+         • {} tables, {} indexes, {} views, {} functions\n\
+         • {} SQL files\n\
+         • Root: {}\n",
+        schema.tables.len(),
+        schema.indexes.len(),
+        schema.views.len(),
+        schema.functions.len(),
+        files.len(),
+        root.display()
+    ));
+
+    Ok(result)
+}
+
+impl Schema {
+    fn table_mut(&mut self, name: &str) -> Option<&mut Table> {
+        self.tables
+            .iter_mut()
+            .find(|table| same_name(&table.name, name))
+    }
+
+    /// Aplica uma instrução ao esquema, como faria a base de dados
+    fn apply(&mut self, statement: Statement) {
+        match statement {
+            Statement::CreateTable(table) => {
+                // `CREATE TABLE IF NOT EXISTS` sobre uma tabela existente não a altera
+                if self.table_mut(&table.name).is_none() {
+                    self.tables.push(table);
+                }
+            }
+            Statement::CreateIndex(index) => {
+                if let Some(name) = &index.name {
+                    self.drop_index(name);
+                }
+                self.indexes.push(index);
+            }
+            Statement::CreateView(view) => {
+                self.views
+                    .retain(|other| !same_name(&other.name, &view.name));
+                self.views.push(view);
+            }
+            Statement::CreateFunction(function) => {
+                self.functions
+                    .retain(|other| !same_name(&other.name, &function.name));
+                self.functions.push(function);
+            }
+            Statement::AlterTable { name, actions } => {
+                for action in actions {
+                    self.alter_table(&name, action);
+                }
+            }
+            Statement::Drop { object, names } => {
+                for name in names {
+                    match object {
+                        ObjectKind::Table => {
+                            self.tables.retain(|table| !same_name(&table.name, &name));
+                            self.indexes.retain(|index| !same_name(&index.table, &name));
+                        }
+                        ObjectKind::Index => self.drop_index(&name),
+                        ObjectKind::View => {
+                            self.views.retain(|view| !same_name(&view.name, &name));
+                        }
+                        ObjectKind::Function => {
+                            self.functions
+                                .retain(|function| !same_name(&function.name, &name));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn drop_index(&mut self, name: &str) {
+        self.indexes.retain(|index| {
+            !index
+                .name
+                .as_deref()
+                .is_some_and(|index_name| same_name(index_name, name))
+        });
+    }
+
+    fn alter_table(&mut self, table_name: &str, action: AlterAction) {
+        if let AlterAction::RenameTable(new_name) = &action {
+            for index in &mut self.indexes {
+                if same_name(&index.table, table_name) {
+                    index.table = new_name.clone();
+                }
+            }
+        }
+
+        let Some(table) = self.table_mut(table_name) else {
+            return;
+        };
+
+        match action {
+            AlterAction::AddColumn(column) => {
+                if !table
+                    .columns
+                    .iter()
+                    .any(|c| same_name(&c.name, &column.name))
+                {
+                    table.columns.push(column);
+                }
+            }
+            AlterAction::AddConstraint(constraint) => {
+                apply_table_constraint(&mut table.columns, &constraint);
+            }
+            AlterAction::DropColumn(name) => {
+                table
+                    .columns
+                    .retain(|column| !same_name(&column.name, &name));
+            }
+            AlterAction::RenameColumn { from, to } => {
+                if let Some(column) = table.columns.iter_mut().find(|c| same_name(&c.name, &from)) {
+                    column.name = to;
+                }
+            }
+            AlterAction::RenameTable(new_name) => table.name = new_name,
+            AlterAction::SetType { column, data_type } => {
+                if let Some(column) = table
+                    .columns
+                    .iter_mut()
+                    .find(|c| same_name(&c.name, &column))
+                {
+                    column.data_type = data_type;
+                }
+            }
+            AlterAction::SetNotNull { column, not_null } => {
+                if let Some(column) = table
+                    .columns
+                    .iter_mut()
+                    .find(|c| same_name(&c.name, &column))
+                {
+                    column.not_null = not_null;
+                }
+            }
+        }
+    }
+}
+
+/// Coluna formatada como `email TEXT (not null, unique)`
+fn describe_column(column: &Column) -> String {
+    let mut notes = Vec::new();
+    if column.primary_key {
+        notes.push("primary key".to_string());
+    }
+    if column.not_null && !column.primary_key {
+        notes.push("not null".to_string());
+    }
+    if column.unique {
+        notes.push("unique".to_string());
+    }
+    if let Some(table) = &column.references {
+        notes.push(format!("→ {}", table));
+    }
+
+    let mut description = column.name.clone();
+    if !column.data_type.is_empty() {
+        description.push(' ');
+        description.push_str(&column.data_type);
+    }
+    if !notes.is_empty() {
+        description.push_str(&format!(" ({})", notes.join(", ")));
+    }
+    description
+}
+
+/// Descrição de um índice: `unique on users (email)`
+fn index_detail(index: &Index) -> String {
+    format!(
+        "{}on {} ({})",
+        if index.unique { "unique " } else { "" },
+        index.table,
+        index.columns
+    )
+}
+
+/// Compara nomes sem distinguir maiúsculas, aceitando a forma qualificada (`public.users`)
+fn same_name(a: &str, b: &str) -> bool {
+    let unqualified = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+    a.eq_ignore_ascii_case(b)
+        || ((!a.contains('.') || !b.contains('.'))
+            && unqualified(a).eq_ignore_ascii_case(&unqualified(b)))
+}
+
+/// Ficheiros `.sql` sob `root`, sem migrações de reversão, em ordem natural
+/// (`2_x.sql` antes de `10_x.sql`)
+fn collect_sql_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .max_depth(8)
+        .into_iter()
+        .filter_entry(|e| {
+            !e.file_type().is_dir()
+                || !IGNORED_DIRS
+                    .iter()
+                    .any(|&ignored| e.file_name().to_string_lossy() == ignored)
+        })
+        .flatten()
+        .map(|entry| entry.into_path())
+        .filter(|path| path.is_file() && is_sql_path(path) && !is_down_migration(path))
+        .collect();
+
+    files.sort_by(|a, b| {
+        let a = a.strip_prefix(root).unwrap_or(a).to_string_lossy();
+        let b = b.strip_prefix(root).unwrap_or(b).to_string_lossy();
+        natural_cmp(&a, &b)
+    });
+    files
+}
+
+/// Verifica se o ficheiro está dentro de uma diretoria de migrações
+fn is_in_migrations_dir(workspace_dir: &Path, path: &Path) -> bool {
+    path.strip_prefix(workspace_dir)
+        .unwrap_or(path)
+        .parent()
+        .is_some_and(|parent| {
+            parent.components().any(|component| {
+                let name = component.as_os_str().to_string_lossy().to_lowercase();
+                MIGRATION_DIRS.contains(&name.as_str())
+            })
+        })
+}
+
+/// Migrações de reversão (`down.sql`, `0001_x.down.sql`, `U1__x.sql` do Flyway)
+fn is_down_migration(path: &Path) -> bool {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let is_flyway_undo = path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy();
+        name.starts_with('U')
+            && name[1..].starts_with(|c: char| c.is_ascii_digit())
+            && name.contains("__")
+    });
+
+    stem == "down"
+        || stem.ends_with(".down")
+        || stem.ends_with("_down")
+        || stem.ends_with("-down")
+        || is_flyway_undo
+}
+
+/// Ordem natural: sequências de dígitos comparadas pelo seu valor numérico
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let a_len = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+                let b_len = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+                let a_digits = a[..a_len].trim_start_matches('0');
+                let b_digits = b[..b_len].trim_start_matches('0');
+                let order = a_digits
+                    .len()
+                    .cmp(&b_digits.len())
+                    .then_with(|| a_digits.cmp(b_digits));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Divide o ficheiro em instruções (`;`) e reconhece as instruções DDL
+fn parse_statements(content: &str) -> Vec<(Statement, Span)> {
+    let tokens = tokenize(content);
+
+    tokens
+        .split(|token| token.is_punct(';'))
+        .filter(|statement| !statement.is_empty())
+        .filter_map(|statement| {
+            parse_statement(statement, content).map(|parsed| (parsed, Span::of(statement)))
+        })
+        .collect()
+}
+
+/// Tokenizador SQL mínimo: ignora comentários e trata strings, identificadores
+/// entre aspas e corpos `$tag$ ... $tag$` como um único token
+fn tokenize(content: &str) -> Vec<Token<'_>> {
+    let bytes = content.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        let start = i;
+
+        let kind = match c {
+            b'\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            _ if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if next == Some(b'-') => {
+                i = content[i..].find('\n').map_or(bytes.len(), |pos| i + pos);
+                continue;
+            }
+            b'/' if next == Some(b'*') => {
+                let end = content[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |pos| i + 2 + pos + 2);
+                line += content[i..end].matches('\n').count();
+                i = end;
+                continue;
+            }
+            b'\'' => {
+                i += 1;
+                while i < bytes.len() {
+                    if bytes[i] == b'\'' {
+                        // `''` é uma aspa escapada dentro da string
+                        if bytes.get(i + 1) == Some(&b'\'') {
+                            i += 2;
+                            continue;
+                        }
+                        i += 1;
+                        break;
+                    }
+                    i += 1;
+                }
+                TokenKind::Literal
+            }
+            b'"' | b'`' => {
+                i = content[i + 1..]
+                    .find(c as char)
+                    .map_or(bytes.len(), |pos| i + 1 + pos + 1);
+                TokenKind::Quoted
+            }
+            b'[' if next.is_some_and(|n| n.is_ascii_alphabetic() || n == b'_') => {
+                i = content[i..]
+                    .find(']')
+                    .map_or(bytes.len(), |pos| i + pos + 1);
+                TokenKind::Quoted
+            }
+            b'$' if dollar_tag(&content[i..]).is_some() => {
+                let tag = dollar_tag(&content[i..]).unwrap_or("$$");
+                i = content[i + tag.len()..]
+                    .find(tag)
+                    .map_or(bytes.len(), |pos| i + tag.len() + pos + tag.len());
+                TokenKind::Literal
+            }
+            _ if c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80 => {
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric()
+                        || bytes[i] == b'_'
+                        || bytes[i] == b'$'
+                        || bytes[i] >= 0x80)
+                {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                i += 1;
+                TokenKind::Punct
+            }
+        };
+
+        let text = &content[start..i];
+        let end_line = line + text.matches('\n').count();
+        tokens.push(Token {
+            kind,
+            text,
+            start,
+            end: i,
+            line,
+            end_line,
+        });
+        line = end_line;
+    }
+
+    tokens
+}
+
+/// Delimitador de uma string `$tag$` do PostgreSQL no início do texto
+fn dollar_tag(text: &str) -> Option<&str> {
+    let rest = &text[1..];
+    let tag_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    let starts_with_digit = rest.starts_with(|c: char| c.is_ascii_digit());
+    (!starts_with_digit && rest[tag_len..].starts_with('$')).then(|| &text[..tag_len + 2])
+}
+
+/// Cursor sobre os tokens de uma instrução
+struct Cursor<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+impl<'t, 'a> Cursor<'t, 'a> {
+    fn new(tokens: &'t [Token<'a>]) -> Self {
+        Cursor { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|token| token.is_keyword(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.is_keyword(keyword);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    /// Consome a sequência de palavras-chave apenas se estiver completa
+    fn eat_keywords(&mut self, keywords: &[&str]) -> bool {
+        let matched = keywords.iter().enumerate().all(|(offset, keyword)| {
+            self.tokens
+                .get(self.pos + offset)
+                .is_some_and(|token| token.is_keyword(keyword))
+        });
+        if matched {
+            self.pos += keywords.len();
+        }
+        matched
+    }
+
+    /// Identificador simples
+    fn ident(&mut self) -> Option<String> {
+        let name = self.peek()?.ident()?;
+        self.pos += 1;
+        Some(name)
+    }
+
+    /// Nome possivelmente qualificado (`schema.tabela`)
+    fn name(&mut self) -> Option<String> {
+        let mut name = self.ident()?;
+        while self.peek().is_some_and(|token| token.is_punct('.')) {
+            self.pos += 1;
+            name.push('.');
+            name.push_str(&self.ident()?);
+        }
+        Some(name)
+    }
+
+    /// Conteúdo entre parênteses, consumindo-os
+    fn group(&mut self) -> Option<&'t [Token<'a>]> {
+        if !self.peek()?.is_punct('(') {
+            return None;
+        }
+        let start = self.pos + 1;
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            self.pos += 1;
+            if token.is_punct('(') {
+                depth += 1;
+            } else if token.is_punct(')') {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&self.tokens[start..self.pos - 1]);
+                }
+            }
+        }
+        Some(&self.tokens[start..])
+    }
+
+    fn rest(&self) -> &'t [Token<'a>] {
+        &self.tokens[self.pos.min(self.tokens.len())..]
+    }
+}
+
+/// Texto original de um intervalo de tokens, com o espaço em branco normalizado
+fn source_text(tokens: &[Token], content: &str) -> String {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => content[first.start..last.end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+/// Divide uma lista entre parênteses pelas vírgulas de nível superior
+fn split_top_level<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<&'t [Token<'a>]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_punct('(') {
+            depth += 1;
+        } else if token.is_punct(')') {
+            depth -= 1;
+        } else if token.is_punct(',') && depth == 0 {
+            parts.push(&tokens[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&tokens[start..]);
+
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Lista de nomes de colunas entre parênteses (`(a, b)`)
+fn column_list(tokens: &[Token]) -> Vec<String> {
+    split_top_level(tokens)
+        .into_iter()
+        .filter_map(|part| part.first().and_then(|token| token.ident()))
+        .collect()
+}
+
+fn parse_statement(tokens: &[Token], content: &str) -> Option<Statement> {
+    let mut cursor = Cursor::new(tokens);
+
+    if cursor.eat_keyword("CREATE") {
+        parse_create(&mut cursor, content)
+    } else if cursor.eat_keywords(&["ALTER", "TABLE"]) {
+        parse_alter_table(&mut cursor, content)
+    } else if cursor.eat_keyword("DROP") {
+        parse_drop(&mut cursor)
+    } else {
+        None
+    }
+}
+
+fn parse_create(cursor: &mut Cursor, content: &str) -> Option<Statement> {
+    let mut unique = false;
+    let mut materialized = false;
+
+    // Modificadores antes do tipo de objeto
+    loop {
+        if cursor.eat_keywords(&["OR", "REPLACE"]) {
+            continue;
+        }
+        if cursor.eat_keyword("UNIQUE") {
+            unique = true;
+            continue;
+        }
+        if cursor.eat_keyword("MATERIALIZED") {
+            materialized = true;
+            continue;
+        }
+        if [
+            "GLOBAL",
+            "LOCAL",
+            "TEMP",
+            "TEMPORARY",
+            "UNLOGGED",
+            "VIRTUAL",
+        ]
+        .iter()
+        .any(|keyword| cursor.eat_keyword(keyword))
+        {
+            continue;
+        }
+        break;
+    }
+
+    if cursor.eat_keyword("TABLE") {
+        cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+        let name = cursor.name()?;
+        let mut columns = Vec::new();
+        if let Some(body) = cursor.group() {
+            let mut constraints = Vec::new();
+            for element in split_top_level(body) {
+                match parse_table_constraint(element) {
+                    Some(constraint) => constraints.push(constraint),
+                    None if is_table_constraint(element) => {}
+                    None => columns.extend(parse_column(element, content)),
+                }
+            }
+            for constraint in &constraints {
+                apply_table_constraint(&mut columns, constraint);
+            }
+        }
+        return Some(Statement::CreateTable(Table { name, columns }));
+    }
+
+    if cursor.eat_keyword("INDEX") {
+        cursor.eat_keyword("CONCURRENTLY");
+        cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+        let name = if cursor.is_keyword("ON") {
+            None
+        } else {
+            cursor.name()
+        };
+        if !cursor.eat_keyword("ON") {
+            return None;
+        }
+        cursor.eat_keyword("ONLY");
+        let table = cursor.name()?;
+        if cursor.eat_keyword("USING") {
+            cursor.ident();
+        }
+        let columns = source_text(cursor.group().unwrap_or_default(), content);
+        return Some(Statement::CreateIndex(Index {
+            name,
+            table,
+            columns,
+            unique,
+        }));
+    }
+
+    if cursor.eat_keyword("VIEW") {
+        cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+        let name = cursor.name()?;
+        return Some(Statement::CreateView(View { name, materialized }));
+    }
+
+    let procedure = cursor.is_keyword("PROCEDURE");
+    if cursor.eat_keyword("FUNCTION") || cursor.eat_keyword("PROCEDURE") {
+        cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+        let name = cursor.name()?;
+        let arguments = source_text(cursor.group().unwrap_or_default(), content);
+        return Some(Statement::CreateFunction(Function {
+            name,
+            arguments,
+            procedure,
+        }));
+    }
+
+    None
+}
+
+fn parse_alter_table(cursor: &mut Cursor, content: &str) -> Option<Statement> {
+    cursor.eat_keywords(&["IF", "EXISTS"]);
+    cursor.eat_keyword("ONLY");
+    let name = cursor.name()?;
+
+    let actions = split_top_level(cursor.rest())
+        .into_iter()
+        .filter_map(|action| parse_alter_action(action, content))
+        .collect();
+
+    Some(Statement::AlterTable { name, actions })
+}
+
+fn parse_alter_action(tokens: &[Token], content: &str) -> Option<AlterAction> {
+    let mut cursor = Cursor::new(tokens);
+
+    if cursor.eat_keyword("ADD") {
+        if let Some(constraint) = parse_table_constraint(cursor.rest()) {
+            return Some(AlterAction::AddConstraint(constraint));
+        }
+        if is_table_constraint(cursor.rest()) {
+            return None;
+        }
+        cursor.eat_keyword("COLUMN");
+        cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+        return parse_column(cursor.rest(), content).map(AlterAction::AddColumn);
+    }
+
+    if cursor.eat_keyword("DROP") {
+        if cursor.is_keyword("CONSTRAINT") {
+            return None;
+        }
+        cursor.eat_keyword("COLUMN");
+        cursor.eat_keywords(&["IF", "EXISTS"]);
+        return cursor.ident().map(AlterAction::DropColumn);
+    }
+
+    if cursor.eat_keyword("RENAME") {
+        if cursor.eat_keyword("TO") || cursor.eat_keyword("AS") {
+            return cursor.name().map(AlterAction::RenameTable);
+        }
+        cursor.eat_keyword("COLUMN");
+        let from = cursor.ident()?;
+        cursor.eat_keyword("TO");
+        let to = cursor.ident()?;
+        return Some(AlterAction::RenameColumn { from, to });
+    }
+
+    // MySQL: `MODIFY col tipo` e `CHANGE antiga nova tipo`
+    if cursor.eat_keyword("MODIFY") {
+        cursor.eat_keyword("COLUMN");
+        let column = parse_column(cursor.rest(), content)?;
+        return Some(AlterAction::SetType {
+            column: column.name,
+            data_type: column.data_type,
+        });
+    }
+    if cursor.eat_keyword("CHANGE") {
+        cursor.eat_keyword("COLUMN");
+        let from = cursor.ident()?;
+        let column = parse_column(cursor.rest(), content)?;
+        return Some(AlterAction::RenameColumn {
+            from,
+            to: column.name,
+        });
+    }
+
+    if cursor.eat_keyword("ALTER") {
+        cursor.eat_keyword("COLUMN");
+        let column = cursor.ident()?;
+        if cursor.eat_keywords(&["SET", "NOT", "NULL"]) {
+            return Some(AlterAction::SetNotNull {
+                column,
+                not_null: true,
+            });
+        }
+        if cursor.eat_keywords(&["DROP", "NOT", "NULL"]) {
+            return Some(AlterAction::SetNotNull {
+                column,
+                not_null: false,
+            });
+        }
+        cursor.eat_keywords(&["SET", "DATA"]);
+        if cursor.eat_keyword("TYPE") {
+            let type_tokens = cursor.rest();
+            let end = type_tokens
+                .iter()
+                .position(|token| token.is_keyword("USING") || token.is_keyword("COLLATE"))
+                .unwrap_or(type_tokens.len());
+            return Some(AlterAction::SetType {
+                column,
+                data_type: source_text(&type_tokens[..end], content),
+            });
+        }
+    }
+
+    None
+}
+
+fn parse_drop(cursor: &mut Cursor) -> Option<Statement> {
+    cursor.eat_keyword("MATERIALIZED");
+    let object = if cursor.eat_keyword("TABLE") {
+        ObjectKind::Table
+    } else if cursor.eat_keyword("INDEX") {
+        ObjectKind::Index
+    } else if cursor.eat_keyword("VIEW") {
+        ObjectKind::View
+    } else if cursor.eat_keyword("FUNCTION") || cursor.eat_keyword("PROCEDURE") {
+        ObjectKind::Function
+    } else {
+        return None;
+    };
+    cursor.eat_keyword("CONCURRENTLY");
+    cursor.eat_keywords(&["IF", "EXISTS"]);
+
+    let names = split_top_level(cursor.rest())
+        .into_iter()
+        .filter_map(|part| Cursor::new(part).name())
+        .collect();
+
+    Some(Statement::Drop { object, names })
+}
+
+/// Definição de coluna: nome, tipo (até à primeira restrição) e restrições inline
+fn parse_column(tokens: &[Token], content: &str) -> Option<Column> {
+    let mut cursor = Cursor::new(tokens);
+    let name = cursor.ident()?;
+    let rest = cursor.rest();
+
+    // O tipo termina na primeira restrição fora de parênteses (`NUMERIC(10, 2) NOT NULL`)
+    let mut depth = 0;
+    let type_end = rest
+        .iter()
+        .position(|token| {
+            if token.is_punct('(') {
+                depth += 1;
+            } else if token.is_punct(')') {
+                depth -= 1;
+            }
+            depth == 0
+                && COLUMN_CONSTRAINT_KEYWORDS
+                    .iter()
+                    .any(|keyword| token.is_keyword(keyword))
+        })
+        .unwrap_or(rest.len());
+    let constraints = &rest[type_end..];
+
+    let references = constraints
+        .iter()
+        .position(|token| token.is_keyword("REFERENCES"))
+        .and_then(|i| Cursor::new(&constraints[i + 1..]).name());
+
+    Some(Column {
+        name,
+        data_type: source_text(&rest[..type_end], content),
+        primary_key: constraints.iter().any(|token| token.is_keyword("PRIMARY")),
+        not_null: constraints
+            .windows(2)
+            .any(|pair| pair[0].is_keyword("NOT") && pair[1].is_keyword("NULL")),
+        unique: constraints.iter().any(|token| token.is_keyword("UNIQUE")),
+        references,
+        span: Span::of(tokens),
+    })
+}
+
+/// Elemento de `CREATE TABLE` que não é uma coluna
+fn is_table_constraint(tokens: &[Token]) -> bool {
+    let Some(first) = tokens.first() else {
+        return false;
+    };
+    let keywords = [
+        "CONSTRAINT",
+        "PRIMARY",
+        "FOREIGN",
+        "UNIQUE",
+        "CHECK",
+        "EXCLUDE",
+        "FULLTEXT",
+        "SPATIAL",
+        "LIKE",
+    ];
+    // `key`/`index` também podem ser nomes de colunas: só `KEY (..)` ou `KEY nome (..)`
+    // em maiúsculas (MySQL) são índices
+    let index_like = (first.text == "KEY" || first.text == "INDEX")
+        && (tokens.get(1).is_some_and(|next| next.is_punct('('))
+            || tokens.get(2).is_some_and(|next| next.is_punct('(')));
+
+    keywords.iter().any(|keyword| first.is_keyword(keyword)) || index_like
+}
+
+/// Reconhece `PRIMARY KEY (...)`, `UNIQUE (...)` e `FOREIGN KEY (...) REFERENCES t`
+fn parse_table_constraint(tokens: &[Token]) -> Option<TableConstraint> {
+    let mut cursor = Cursor::new(tokens);
+    if cursor.eat_keyword("CONSTRAINT") {
+        cursor.ident();
+    }
+
+    if cursor.eat_keywords(&["PRIMARY", "KEY"]) {
+        return Some(TableConstraint::PrimaryKey(column_list(cursor.group()?)));
+    }
+    if cursor.eat_keyword("UNIQUE") {
+        if !cursor.eat_keyword("KEY") {
+            cursor.eat_keyword("INDEX");
+        }
+        if !cursor.peek()?.is_punct('(') {
+            cursor.ident();
+        }
+        return Some(TableConstraint::Unique(column_list(cursor.group()?)));
+    }
+    if cursor.eat_keywords(&["FOREIGN", "KEY"]) {
+        let columns = column_list(cursor.group()?);
+        if !cursor.eat_keyword("REFERENCES") {
+            return None;
+        }
+        let table = cursor.name()?;
+        return Some(TableConstraint::ForeignKey { columns, table });
+    }
+
+    None
+}
+
+/// Marca nas colunas as restrições declaradas ao nível da tabela
+fn apply_table_constraint(columns: &mut [Column], constraint: &TableConstraint) {
+    let named =
+        |names: &[String], column: &Column| names.iter().any(|name| same_name(name, &column.name));
+
+    match constraint {
+        TableConstraint::PrimaryKey(names) => {
+            for column in columns.iter_mut().filter(|column| named(names, column)) {
+                column.primary_key = true;
+            }
+        }
+        // Uma restrição composta não torna cada coluna única
+        TableConstraint::Unique(names) if names.len() == 1 => {
+            for column in columns.iter_mut().filter(|column| named(names, column)) {
+                column.unique = true;
+            }
+        }
+        TableConstraint::Unique(_) => {}
+        TableConstraint::ForeignKey {
+            columns: names,
+            table,
+        } => {
+            for column in columns.iter_mut().filter(|column| named(names, column)) {
+                column.references = Some(table.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(content)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    fn schema(content: &str) -> Schema {
+        let mut schema = Schema::default();
+        for (statement, _) in parse_statements(content) {
+            schema.apply(statement);
+        }
+        schema
+    }
+
+    fn columns(schema: &Schema, table: &str) -> Vec<String> {
+        schema
+            .tables
+            .iter()
+            .find(|t| t.name == table)
+            .map(|t| t.columns.iter().map(describe_column).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn tokenize_skips_comments_and_counts_lines() {
+        let tokens = tokenize("-- header\nSELECT /* a\nb */ x;\n");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text).collect();
+        assert_eq!(texts, ["SELECT", "x", ";"]);
+        assert_eq!(tokens[0].line, 2);
+        assert_eq!(tokens[1].line, 3);
+    }
+
+    #[test]
+    fn tokenize_keeps_strings_and_quoted_names_whole() {
+        assert_eq!(
+            kinds("'it''s' \"a b\" `c` [d e] $fn$ x; y $fn$"),
+            [
+                (TokenKind::Literal, "'it''s'"),
+                (TokenKind::Quoted, "\"a b\""),
+                (TokenKind::Quoted, "`c`"),
+                (TokenKind::Quoted, "[d e]"),
+                (TokenKind::Literal, "$fn$ x; y $fn$"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenize_runs_unterminated_tokens_to_the_end() {
+        assert_eq!(kinds("'abc"), [(TokenKind::Literal, "'abc")]);
+        assert_eq!(
+            kinds("x \"é"),
+            [(TokenKind::Word, "x"), (TokenKind::Quoted, "\"é")]
+        );
+        assert_eq!(kinds("/* open"), []);
+    }
+
+    #[test]
+    fn ident_strips_quotes() {
+        let idents: Vec<Option<String>> = tokenize("name \"Mixed Case\" `tick` [bracket] ,")
+            .iter()
+            .map(Token::ident)
+            .collect();
+        assert_eq!(
+            idents,
+            [
+                Some("name".to_string()),
+                Some("Mixed Case".to_string()),
+                Some("tick".to_string()),
+                Some("bracket".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn ident_accepts_unterminated_quotes() {
+        let ident = |content: &str| tokenize(content)[0].ident();
+        assert_eq!(ident("\""), Some(String::new()));
+        assert_eq!(ident("\"é"), Some("é".to_string()));
+        assert_eq!(ident("[abc"), Some("abc".to_string()));
+    }
+
+    #[test]
+    fn unterminated_quoted_name_does_not_panic() {
+        let symbols = extract_sql_symbols("CREATE TABLE users (id int);\nCREATE TABLE \"é");
+        assert_eq!(symbols[0].name, "users");
+        schema("CREATE TABLE \"");
+    }
+
+    #[test]
+    fn create_table_reads_columns_and_constraints() {
+        let schema = schema(
+            "CREATE TABLE IF NOT EXISTS public.orders (
+                id SERIAL PRIMARY KEY,
+                total NUMERIC(10, 2) NOT NULL DEFAULT 0,
+                email TEXT UNIQUE,
+                user_id INT REFERENCES users(id),
+                tenant INT,
+                code TEXT,
+                FOREIGN KEY (tenant) REFERENCES tenants (id),
+                UNIQUE (code),
+                CHECK (total >= 0)
+            );",
+        );
+        assert_eq!(schema.tables[0].name, "public.orders");
+        assert_eq!(
+            columns(&schema, "public.orders"),
+            [
+                "id SERIAL (primary key)",
+                "total NUMERIC(10, 2) (not null)",
+                "email TEXT (unique)",
+                "user_id INT (→ users)",
+                "tenant INT (→ tenants)",
+                "code TEXT (unique)",
+            ]
+        );
+    }
+
+    #[test]
+    fn alter_table_changes_columns() {
+        let schema = schema(
+            "CREATE TABLE users (id INT, name TEXT, legacy TEXT);
+             ALTER TABLE users ADD COLUMN email TEXT NOT NULL, DROP COLUMN legacy;
+             ALTER TABLE users RENAME COLUMN name TO full_name;
+             ALTER TABLE users ALTER COLUMN id TYPE BIGINT;
+             ALTER TABLE users RENAME TO accounts;",
+        );
+        assert_eq!(
+            columns(&schema, "accounts"),
+            ["id BIGINT", "full_name TEXT", "email TEXT (not null)"]
+        );
+    }
+
+    #[test]
+    fn drop_and_replace_follow_migration_order() {
+        let schema = schema(
+            "CREATE TABLE a (id INT);
+             CREATE TABLE b (id INT);
+             CREATE UNIQUE INDEX a_id ON a (id);
+             CREATE INDEX b_id ON b (id);
+             DROP TABLE IF EXISTS b;
+             CREATE VIEW v AS SELECT 1;
+             CREATE MATERIALIZED VIEW m AS SELECT 1;
+             DROP VIEW v;
+             CREATE FUNCTION f(x int) RETURNS int AS $$ SELECT x; $$ LANGUAGE sql;
+             CREATE OR REPLACE FUNCTION f(x int, y int) RETURNS int AS $$ SELECT x; $$ LANGUAGE sql;",
+        );
+        let tables: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(tables, ["a"]);
+        let indexes: Vec<String> = schema.indexes.iter().map(index_detail).collect();
+        assert_eq!(indexes, ["unique on a (id)"]);
+        let views: Vec<(&str, bool)> = schema
+            .views
+            .iter()
+            .map(|v| (v.name.as_str(), v.materialized))
+            .collect();
+        assert_eq!(views, [("m", true)]);
+        assert_eq!(schema.functions.len(), 1);
+        assert_eq!(schema.functions[0].arguments, "x int, y int");
+    }
+
+    #[test]
+    fn extract_symbols_nests_columns_under_tables() {
+        let symbols = extract_sql_symbols("CREATE TABLE t (\n  id INT,\n  name TEXT\n);");
+        let outline: Vec<(&str, &str, Option<&str>, usize)> = symbols
+            .iter()
+            .map(|s| {
+                (
+                    s.kind.as_str(),
+                    s.name.as_str(),
+                    s.container.as_deref(),
+                    s.start_line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            [
+                ("table", "t", None, 1),
+                ("column", "id", Some("t"), 2),
+                ("column", "name", Some("t"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn natural_order_sorts_numbers_by_value() {
+        assert_eq!(natural_cmp("2_b.sql", "10_a.sql"), Ordering::Less);
+        assert_eq!(natural_cmp("V1__x.sql", "V1__y.sql"), Ordering::Less);
+    }
+}
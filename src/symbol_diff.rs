@@ -0,0 +1,358 @@
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::language_config::LanguageConfig;
+use crate::{Symbol, extract_symbols_from_source, format_symbol};
+
+/// Tipo de alteração detetada num símbolo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Alteração de um símbolo entre a referência base e a árvore de trabalho
+#[derive(Debug)]
+struct SymbolChange {
+    kind: ChangeKind,
+    symbol: Symbol,
+}
+
+/// Compara os símbolos da árvore de trabalho com os de uma referência git
+/// e retorna um relatório de funções/tipos adicionados, removidos e modificados
+pub(crate) fn format_symbol_diff(workspace_dir: &Path, base_ref: &str) -> Result<String> {
+    // Validar a referência antes de fazer qualquer outra coisa
+    run_git(
+        workspace_dir,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{}^{{commit}}", base_ref),
+        ],
+    )
+    .map_err(|_| anyhow!("Unknown git ref: {}", base_ref))?;
+
+    let changed_files = collect_changed_files(workspace_dir, base_ref)?;
+    let config = LanguageConfig::load(workspace_dir);
+
+    let mut report = BTreeMap::new();
+    for (status, relative_path) in changed_files {
+        let path = Path::new(&relative_path);
+
+        let old_content = if status == 'A' {
+            String::new()
+        } else {
+            run_git(
+                workspace_dir,
+                &["show", &format!("{}:./{}", base_ref, relative_path)],
+            )
+            .unwrap_or_default()
+        };
+        let new_content = if status == 'D' {
+            String::new()
+        } else {
+            fs::read_to_string(workspace_dir.join(path)).unwrap_or_default()
+        };
+
+        let (Ok(old_symbols), Ok(new_symbols)) = (
+            extract_symbols_from_source(path, &old_content, &config),
+            extract_symbols_from_source(path, &new_content, &config),
+        ) else {
+            continue;
+        };
+
+        let changes = diff_symbols(&old_symbols, &old_content, &new_symbols, &new_content);
+        if !changes.is_empty() {
+            report.insert(relative_path, changes);
+        }
+    }
+
+    let mut result = String::new();
+    result.push_str(&format!("🔀 Symbol Changes vs {}\n", base_ref));
+    result.push_str("══════════════════════════════════\n\n");
+
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+
+    if report.is_empty() {
+        result.push_str("No symbol changes found.\n");
+    }
+
+    for (file, changes) in &report {
+        result.push_str(&format!("{}\n", file));
+        for change in changes {
+            let marker = match change.kind {
+                ChangeKind::Added => {
+                    added += 1;
+                    "➕"
+                }
+                ChangeKind::Removed => {
+                    removed += 1;
+                    "➖"
+                }
+                ChangeKind::Modified => {
+                    modified += 1;
+                    "✏️ "
+                }
+            };
+            // Símbolos removidos só existem na base: as linhas são as da base
+            let lines = if change.kind == ChangeKind::Removed {
+                "base L"
+            } else {
+                "L"
+            };
+            result.push_str(&format!(
+                "  {} {} ({}{}-{})\n",
+                marker,
+                format_symbol(&format!(
+                    "{} {}",
+                    change.symbol.kind,
+                    qualified_name(&change.symbol)
+                )),
+                lines,
+                change.symbol.start_line,
+                change.symbol.end_line
+            ));
+        }
+        result.push('\n');
+    }
+
+    result.push_str(&format!(
+        "📊 Summary:\n\
+         • {} files with symbol changes\n\
+         • {} added, {} removed, {} modified\n\
+         • Base: {}\n\
+         • Root: {}\n",
+        report.len(),
+        added,
+        removed,
+        modified,
+        base_ref,
+        workspace_dir.display()
+    ));
+
+    Ok(result)
+}
+
+/// Lista os ficheiros alterados desde `base_ref` (incluindo ficheiros não seguidos),
+/// com caminhos relativos ao diretório do workspace
+fn collect_changed_files(workspace_dir: &Path, base_ref: &str) -> Result<Vec<(char, String)>> {
+    let mut files = Vec::new();
+
+    let diff = run_git(
+        workspace_dir,
+        &[
+            "diff",
+            "--name-status",
+            "--no-renames",
+            "--relative",
+            base_ref,
+            "--",
+        ],
+    )?;
+    for line in diff.lines() {
+        let mut parts = line.splitn(2, '\t');
+        if let (Some(status), Some(file)) = (parts.next(), parts.next()) {
+            files.push((status.chars().next().unwrap_or('M'), file.to_string()));
+        }
+    }
+
+    let untracked = run_git(
+        workspace_dir,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?;
+    for file in untracked.lines().filter(|line| !line.is_empty()) {
+        files.push(('A', file.to_string()));
+    }
+
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// Emparelha símbolos pelo rótulo (`fn nome`) e pela ordem de ocorrência,
+/// comparando o texto da definição para detetar modificações. Um `impl` ou classe
+/// só é modificado se mudar fora dos símbolos aninhados, que são reportados à parte.
+/// Adicionados e modificados vêm pela ordem no ficheiro atual, seguidos dos removidos
+/// pela ordem na base.
+fn diff_symbols(
+    old_symbols: &[Symbol],
+    old_content: &str,
+    new_symbols: &[Symbol],
+    new_content: &str,
+) -> Vec<SymbolChange> {
+    let old_by_key = index_symbols(old_symbols);
+    let new_by_key = index_symbols(new_symbols);
+
+    let mut changes = Vec::new();
+
+    for (key, new_symbol) in &new_by_key {
+        match old_by_key.get(key) {
+            None => changes.push(SymbolChange {
+                kind: ChangeKind::Added,
+                symbol: (*new_symbol).clone(),
+            }),
+            Some(old_symbol) => {
+                let old_text = &old_content[old_symbol.start_byte..old_symbol.end_byte];
+                let new_text = &new_content[new_symbol.start_byte..new_symbol.end_byte];
+                if old_text != new_text
+                    && text_outside_nested(old_symbol, old_symbols, old_content)
+                        != text_outside_nested(new_symbol, new_symbols, new_content)
+                {
+                    changes.push(SymbolChange {
+                        kind: ChangeKind::Modified,
+                        symbol: (*new_symbol).clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    for (key, old_symbol) in &old_by_key {
+        if !new_by_key.contains_key(key) {
+            changes.push(SymbolChange {
+                kind: ChangeKind::Removed,
+                symbol: (*old_symbol).clone(),
+            });
+        }
+    }
+
+    changes.sort_by_key(|change| (change.kind == ChangeKind::Removed, change.symbol.start_line));
+    changes
+}
+
+/// Texto do símbolo sem os símbolos aninhados (métodos de um `impl`, campos de uma
+/// struct) e com os espaços normalizados, para comparar apenas o que lhe pertence
+fn text_outside_nested(symbol: &Symbol, symbols: &[Symbol], content: &str) -> String {
+    let mut nested: Vec<(usize, usize)> = symbols
+        .iter()
+        .filter(|other| {
+            symbol.start_byte <= other.start_byte
+                && other.end_byte <= symbol.end_byte
+                && (other.start_byte, other.end_byte) != (symbol.start_byte, symbol.end_byte)
+        })
+        .map(|other| (other.start_byte, other.end_byte))
+        .collect();
+    nested.sort_unstable();
+
+    let mut text = String::new();
+    let mut cursor = symbol.start_byte;
+    for (start, end) in nested {
+        // Símbolos dentro de outro aninhado já foram retirados com ele
+        if start < cursor {
+            continue;
+        }
+        text.push_str(&content[cursor..start]);
+        text.push(' ');
+        cursor = end;
+    }
+    text.push_str(&content[cursor..symbol.end_byte]);
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Indexa os símbolos por (rótulo, n-ésima ocorrência) para distinguir, por exemplo,
+/// vários blocos `impl` do mesmo tipo
+fn index_symbols(symbols: &[Symbol]) -> BTreeMap<(String, usize), &Symbol> {
+    let mut occurrences: BTreeMap<String, usize> = BTreeMap::new();
+    let mut index = BTreeMap::new();
+
+    for symbol in symbols {
+        let label = format!("{} {}", symbol.kind, qualified_name(symbol));
+        let count = occurrences.entry(label.clone()).or_insert(0);
+        index.insert((label, *count), symbol);
+        *count += 1;
+    }
+
+    index
+}
+
+/// Nome do símbolo qualificado pela classe que o contém (`User.save`)
+fn qualified_name(symbol: &Symbol) -> String {
+    match &symbol.container {
+        Some(container) => format!("{}.{}", container, symbol.name),
+        None => symbol.name.clone(),
+    }
+}
+
+/// Executa um comando git no diretório do workspace e devolve o stdout
+fn run_git(workspace_dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace_dir)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> Vec<(ChangeKind, String, usize)> {
+        let config = LanguageConfig::default();
+        let path = Path::new("lib.rs");
+        let old_symbols = extract_symbols_from_source(path, old, &config).unwrap();
+        let new_symbols = extract_symbols_from_source(path, new, &config).unwrap();
+        diff_symbols(&old_symbols, old, &new_symbols, new)
+            .into_iter()
+            .map(|change| (change.kind, change.symbol.name, change.symbol.start_line))
+            .collect()
+    }
+
+    #[test]
+    fn containers_changed_only_inside_nested_symbols_are_not_modified() {
+        let old = "impl User {\n    fn save(&self) { a(); }\n\n    fn gone(&self) {}\n}\n";
+        let new = "impl User {\n    fn save(&self) { b(); }\n\n    fn added(&self) {}\n}\n";
+        let changes = diff(old, new);
+        assert!(
+            changes.iter().all(|(_, name, _)| name != "User"),
+            "{:?}",
+            changes
+        );
+
+        let changes = diff(
+            old,
+            "impl<T> User {\n    fn save(&self) { a(); }\n\n    fn gone(&self) {}\n}\n",
+        );
+        assert!(
+            changes
+                .iter()
+                .any(|(kind, name, _)| *kind == ChangeKind::Modified && name == "User")
+        );
+    }
+
+    #[test]
+    fn removals_come_after_current_symbols() {
+        let old = "fn a() {}\nfn b() {}\nfn c() {}\n";
+        let new = "fn d() {}\nfn e() {}\nfn f() {}\nfn c() { x(); }\n";
+        let changes = diff(old, new);
+        let order: Vec<(ChangeKind, &str)> = changes
+            .iter()
+            .map(|(kind, name, _)| (*kind, name.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (ChangeKind::Added, "d"),
+                (ChangeKind::Added, "e"),
+                (ChangeKind::Added, "f"),
+                (ChangeKind::Modified, "c"),
+                (ChangeKind::Removed, "a"),
+                (ChangeKind::Removed, "b"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,579 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::content_filter;
+use crate::language_config::LanguageConfig;
+use crate::syntax_trees::SyntaxTrees;
+use crate::workspace_watcher::lock;
+use crate::{ParseTimedOut, Symbol, extract_symbols_with_tree, sort_symbols};
+
+/// Muda sempre que o formato do índice ou a extração de símbolos mudam
+const INDEX_FORMAT: u32 = 3;
+
+/// Motivo registado para os ficheiros cuja análise entrou em pânico
+const PARSER_PANICKED: &str = "parser panicked";
+
+/// Símbolos de um ficheiro e a assinatura do conteúdo de onde foram extraídos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    mtime_secs: u64,
+    mtime_nanos: u32,
+    size: u64,
+    hash: u64,
+    /// `Err` guarda a mensagem de erro, para não voltar a analisar ficheiros inválidos
+    symbols: Result<Vec<Symbol>, String>,
+    /// Motivo pelo qual o conteúdo não foi analisado (binário, minificado ou gerado)
+    skip_reason: Option<String>,
+}
+
+/// Resultado de comparar um ficheiro com a sua entrada no índice
+enum Change {
+    Unchanged,
+    /// Só a data de modificação mudou; o conteúdo é o mesmo
+    Touched {
+        size: u64,
+        mtime_secs: u64,
+        mtime_nanos: u32,
+    },
+    Parsed(IndexEntry),
+    Removed,
+    /// O `deadline` interrompeu o parse; o ficheiro fica por verificar
+    Unfinished,
+}
+
+/// Trabalho feito na última atualização do índice, mostrado no resumo
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IndexStats {
+    /// Ficheiros verificados
+    pub(crate) files: usize,
+    /// Ficheiros novos ou alterados que foram analisados
+    pub(crate) parsed: usize,
+    /// Ficheiros por verificar por ter sido atingido o limite de tempo
+    pub(crate) unfinished: usize,
+    pub(crate) threads: usize,
+    pub(crate) elapsed: Duration,
+}
+
+/// Conteúdo do ficheiro do índice
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    format: u32,
+    version: String,
+    /// Impressão digital das queries e gramáticas usadas na extração
+    config: u64,
+    files: HashMap<PathBuf, IndexEntry>,
+}
+
+/// Índice persistente de símbolos de um workspace, guardado na cache do utilizador.
+/// Só os ficheiros cujo tamanho, data de modificação e conteúdo mudaram são analisados de novo.
+pub(crate) struct SymbolIndex {
+    workspace_dir: PathBuf,
+    cache_path: Option<PathBuf>,
+    data: IndexFile,
+    dirty: bool,
+    stats: IndexStats,
+    /// Ficheiros comparados com o disco desde que o índice foi aberto; as entradas
+    /// lidas da cache só são de confiança depois de verificadas
+    checked: HashSet<PathBuf>,
+    /// Conteúdo ainda não gravado dos documentos abertos no editor, que prevalece
+    /// sobre o do disco
+    documents: HashMap<PathBuf, String>,
+    /// Últimas árvores dos ficheiros editados, para parse incremental
+    trees: Mutex<SyntaxTrees>,
+}
+
+impl SymbolIndex {
+    /// Abre o índice do workspace; é descartado se foi criado por outra versão
+    /// do servidor ou com outras queries
+    pub(crate) fn open(workspace_dir: &Path, config: &LanguageConfig) -> Self {
+        let cache_path = user_cache_dir().map(|dir| {
+            dir.join("index").join(format!(
+                "{:016x}.json",
+                fnv1a(workspace_dir.to_string_lossy().as_bytes())
+            ))
+        });
+        let config_fingerprint = config.fingerprint();
+
+        let data = cache_path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| serde_json::from_slice::<IndexFile>(&bytes).ok())
+            .filter(|data| {
+                data.format == INDEX_FORMAT
+                    && data.version == env!("CARGO_PKG_VERSION")
+                    && data.config == config_fingerprint
+            })
+            .unwrap_or_else(|| IndexFile {
+                format: INDEX_FORMAT,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                config: config_fingerprint,
+                files: HashMap::new(),
+            });
+
+        SymbolIndex {
+            workspace_dir: workspace_dir.to_path_buf(),
+            cache_path,
+            data,
+            dirty: false,
+            stats: IndexStats::default(),
+            checked: HashSet::new(),
+            documents: HashMap::new(),
+            trees: Mutex::default(),
+        }
+    }
+
+    /// Atualiza os símbolos dos ficheiros indicados e guarda o índice se algo mudou
+    pub(crate) fn refresh(
+        &mut self,
+        files: &[PathBuf],
+        config: &LanguageConfig,
+        deadline: Option<Instant>,
+    ) {
+        let started = Instant::now();
+        let files: Vec<&PathBuf> = files.iter().collect();
+        self.update_all(&files, config, deadline);
+
+        // Esquecer ficheiros que já não existem
+        let workspace_dir = &self.workspace_dir;
+        let before = self.data.files.len();
+        self.data
+            .files
+            .retain(|relative, _| workspace_dir.join(relative).is_file());
+        self.dirty |= self.data.files.len() != before;
+
+        self.save_if_dirty();
+        self.stats.elapsed = started.elapsed();
+    }
+
+    /// Verifica apenas os ficheiros ainda não verificados por este processo; os
+    /// restantes são mantidos atualizados por quem observa o workspace
+    pub(crate) fn index_unchecked(
+        &mut self,
+        files: &[PathBuf],
+        config: &LanguageConfig,
+        deadline: Option<Instant>,
+    ) {
+        let started = Instant::now();
+        let unchecked: Vec<&PathBuf> = files
+            .iter()
+            .filter(|file| !self.checked.contains(self.relative(file)))
+            .collect();
+        self.update_all(&unchecked, config, deadline);
+        self.save_if_dirty();
+        self.stats.elapsed = started.elapsed();
+    }
+
+    /// Volta a exigir que o ficheiro seja verificado no próximo `index_unchecked`
+    pub(crate) fn mark_unchecked(&mut self, file_path: &Path) {
+        let relative = self.relative(file_path).to_path_buf();
+        self.checked.remove(&relative);
+    }
+
+    /// Esquece um ficheiro apagado ou, se for uma diretoria, todos os ficheiros dentro dela
+    pub(crate) fn remove(&mut self, path: &Path) {
+        let relative = self.relative(path).to_path_buf();
+        let before = self.data.files.len();
+        self.data
+            .files
+            .retain(|indexed, _| !indexed.starts_with(&relative));
+        self.checked
+            .retain(|checked| !checked.starts_with(&relative));
+        lock(&self.trees).remove(&relative);
+        self.dirty |= self.data.files.len() != before;
+    }
+
+    pub(crate) fn save_if_dirty(&mut self) {
+        if self.dirty {
+            self.save();
+        }
+    }
+
+    /// Símbolos de um ficheiro já indexado por `refresh`
+    pub(crate) fn symbols(&self, file_path: &Path) -> Option<Result<&[Symbol], &str>> {
+        let entry = self.data.files.get(self.relative(file_path))?;
+        Some(match &entry.symbols {
+            Ok(symbols) => Ok(symbols.as_slice()),
+            Err(error) => Err(error.as_str()),
+        })
+    }
+
+    /// Motivo pelo qual o conteúdo do ficheiro não foi analisado, se for o caso
+    pub(crate) fn skip_reason(&self, file_path: &Path) -> Option<&str> {
+        self.data
+            .files
+            .get(self.relative(file_path))?
+            .skip_reason
+            .as_deref()
+    }
+
+    /// Tempo e trabalho da última atualização do índice
+    pub(crate) fn stats(&self) -> &IndexStats {
+        &self.stats
+    }
+
+    /// Hash do conteúdo de onde foram extraídos os símbolos do ficheiro
+    pub(crate) fn content_hash(&self, file_path: &Path) -> Option<u64> {
+        Some(self.data.files.get(self.relative(file_path))?.hash)
+    }
+
+    /// Ficheiro do índice na cache; outros índices do workspace são guardados ao lado
+    pub(crate) fn cache_path(&self) -> Option<&Path> {
+        self.cache_path.as_deref()
+    }
+
+    fn relative<'a>(&self, file_path: &'a Path) -> &'a Path {
+        file_path
+            .strip_prefix(&self.workspace_dir)
+            .unwrap_or(file_path)
+    }
+
+    /// Atualiza um único ficheiro
+    pub(crate) fn update(&mut self, file_path: &Path, config: &LanguageConfig) {
+        let change = self.inspect(file_path, config, None);
+        self.apply(file_path, change);
+    }
+
+    /// Verifica e analisa os ficheiros em paralelo, um parser por thread; as
+    /// alterações são aplicadas no fim, na thread atual. Atingido o `deadline`, o
+    /// parse em curso é interrompido e os ficheiros que faltam ficam por verificar.
+    fn update_all(
+        &mut self,
+        files: &[&PathBuf],
+        config: &LanguageConfig,
+        deadline: Option<Instant>,
+    ) {
+        let threads = thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1)
+            .min(files.len())
+            .max(1);
+        let next = AtomicUsize::new(0);
+        let parsed = AtomicUsize::new(0);
+
+        let changes: Vec<(&PathBuf, Change)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut changes = Vec::new();
+                        // Cada thread vai buscar o próximo ficheiro, para equilibrar a carga
+                        while deadline.is_none_or(|deadline| Instant::now() < deadline)
+                            && let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            let change = self.inspect(file, config, deadline);
+                            match change {
+                                Change::Unfinished => continue,
+                                Change::Parsed(_) => {
+                                    parsed.fetch_add(1, Ordering::Relaxed);
+                                }
+                                _ => {}
+                            }
+                            changes.push((*file, change));
+                        }
+                        changes
+                    })
+                })
+                .collect();
+            // Os pânicos dos parsers já são tratados em `inspect`; outro pânico é um erro
+            workers
+                .into_iter()
+                .flat_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|payload| panic::resume_unwind(payload))
+                })
+                .collect()
+        });
+
+        let unfinished = files.len() - changes.len();
+        for (file, change) in changes {
+            self.apply(file, change);
+        }
+
+        self.stats = IndexStats {
+            files: files.len(),
+            parsed: parsed.into_inner(),
+            unfinished,
+            threads,
+            elapsed: Duration::ZERO,
+        };
+    }
+
+    /// Como `compare`, mas um pânico durante a análise não termina o servidor: o
+    /// ficheiro fica registado como ignorado até voltar a mudar
+    fn inspect(
+        &self,
+        file_path: &Path,
+        config: &LanguageConfig,
+        deadline: Option<Instant>,
+    ) -> Change {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            self.compare(file_path, config, deadline)
+        }))
+        .unwrap_or_else(|_| self.panicked(file_path))
+    }
+
+    /// Entrada sem símbolos para um ficheiro cuja análise entrou em pânico
+    fn panicked(&self, file_path: &Path) -> Change {
+        eprintln!(
+            "⚠️  Parser panicked on {}; file skipped",
+            file_path.display()
+        );
+        let (size, (mtime_secs, mtime_nanos), hash) =
+            match self.documents.get(self.relative(file_path)) {
+                Some(text) => (u64::MAX, (0, 0), fnv1a(text.as_bytes())),
+                None => {
+                    let Ok(metadata) = fs::metadata(file_path) else {
+                        return Change::Removed;
+                    };
+                    let hash = fs::read(file_path).map_or(0, |bytes| fnv1a(&bytes));
+                    (metadata.len(), modified(&metadata), hash)
+                }
+            };
+        Change::Parsed(IndexEntry {
+            mtime_secs,
+            mtime_nanos,
+            size,
+            hash,
+            symbols: Ok(Vec::new()),
+            skip_reason: Some(PARSER_PANICKED.to_string()),
+        })
+    }
+
+    /// Compara o ficheiro com a entrada do índice: reutiliza-a se o tamanho e a data
+    /// coincidem ou, se não, se o conteúdo tem o mesmo hash; caso contrário extrai
+    /// os símbolos de novo
+    fn compare(
+        &self,
+        file_path: &Path,
+        config: &LanguageConfig,
+        deadline: Option<Instant>,
+    ) -> Change {
+        let existing = self.data.files.get(self.relative(file_path));
+
+        // Documentos abertos: a entrada não guarda data nem tamanho, para que o
+        // conteúdo do disco volte a ser verificado quando o documento for fechado
+        if let Some(text) = self.documents.get(self.relative(file_path)) {
+            let hash = fnv1a(text.as_bytes());
+            if existing.is_some_and(|entry| entry.hash == hash) {
+                return Change::Unchanged;
+            }
+            let Some(symbols) = self.extract(file_path, text, config, true, deadline) else {
+                return Change::Unfinished;
+            };
+            return Change::Parsed(IndexEntry {
+                mtime_secs: 0,
+                mtime_nanos: 0,
+                size: u64::MAX,
+                hash,
+                symbols,
+                skip_reason: None,
+            });
+        }
+
+        let Ok(metadata) = fs::metadata(file_path) else {
+            return Change::Removed;
+        };
+        let size = metadata.len();
+        let (mtime_secs, mtime_nanos) = modified(&metadata);
+
+        if let Some(entry) = existing
+            && entry.size == size
+            && entry.mtime_secs == mtime_secs
+            && entry.mtime_nanos == mtime_nanos
+        {
+            return Change::Unchanged;
+        }
+
+        let (hash, symbols, skip_reason) = match fs::read(file_path) {
+            Ok(bytes) => {
+                let hash = fnv1a(&bytes);
+                if let Some(entry) = existing
+                    && entry.hash == hash
+                {
+                    return Change::Touched {
+                        size,
+                        mtime_secs,
+                        mtime_nanos,
+                    };
+                }
+                if let Some(reason) = content_filter::skip_reason(file_path, &bytes) {
+                    (hash, Ok(Vec::new()), Some(reason))
+                } else {
+                    // Só se guarda a árvore de ficheiros que já mudaram desde que foram indexados
+                    let symbols = match String::from_utf8(bytes) {
+                        Ok(content) => {
+                            let keep_tree = existing.is_some();
+                            match self.extract(file_path, &content, config, keep_tree, deadline) {
+                                Some(symbols) => symbols,
+                                None => return Change::Unfinished,
+                            }
+                        }
+                        Err(error) => Err(error.to_string()),
+                    };
+                    (hash, symbols, None)
+                }
+            }
+            Err(error) => (0, Err(error.to_string()), None),
+        };
+
+        Change::Parsed(IndexEntry {
+            mtime_secs,
+            mtime_nanos,
+            size,
+            hash,
+            symbols,
+            skip_reason,
+        })
+    }
+
+    /// Extrai os símbolos ordenados, reutilizando a árvore anterior do ficheiro se existir.
+    /// Com `keep_tree` a árvore nova é guardada para a próxima alteração. Devolve `None`
+    /// se o `deadline` interromper o parse.
+    fn extract(
+        &self,
+        file_path: &Path,
+        content: &str,
+        config: &LanguageConfig,
+        keep_tree: bool,
+        deadline: Option<Instant>,
+    ) -> Option<Result<Vec<Symbol>, String>> {
+        let relative = self.relative(file_path);
+        let old_tree = lock(&self.trees).take_edited(relative, content);
+        let keep_tree = keep_tree || old_tree.is_some();
+
+        let (symbols, tree) = match extract_symbols_with_tree(
+            file_path,
+            content,
+            config,
+            old_tree.as_ref(),
+            deadline,
+        ) {
+            Ok(extracted) => extracted,
+            Err(error) if error.is::<ParseTimedOut>() => return None,
+            Err(error) => return Some(Err(error.to_string())),
+        };
+        if keep_tree && let Some(tree) = tree {
+            lock(&self.trees).insert(relative, content, tree);
+        }
+
+        Some(Ok(sort_symbols(file_path, symbols)))
+    }
+
+    /// Usa o conteúdo de um documento aberto no editor em vez do ficheiro gravado
+    pub(crate) fn set_document(&mut self, file_path: &Path, text: String, config: &LanguageConfig) {
+        let relative = self.relative(file_path).to_path_buf();
+        self.documents.insert(relative, text);
+        self.update(file_path, config);
+    }
+
+    /// Conteúdo atual do ficheiro: o do documento aberto ou, se não existir, o do disco
+    pub(crate) fn document_text(&self, file_path: &Path) -> Option<String> {
+        match self.documents.get(self.relative(file_path)) {
+            Some(text) => Some(text.clone()),
+            None => fs::read_to_string(file_path).ok(),
+        }
+    }
+
+    /// Volta a usar o conteúdo do disco quando o documento é fechado
+    pub(crate) fn close_document(&mut self, file_path: &Path, config: &LanguageConfig) {
+        let relative = self.relative(file_path).to_path_buf();
+        if self.documents.remove(&relative).is_some() {
+            self.update(file_path, config);
+        }
+    }
+
+    fn apply(&mut self, file_path: &Path, change: Change) {
+        let relative = self.relative(file_path).to_path_buf();
+        match change {
+            Change::Removed => self.checked.remove(&relative),
+            // Ficheiro por verificar: o índice fica como estava
+            Change::Unfinished => return,
+            _ => self.checked.insert(relative.clone()),
+        };
+        match change {
+            Change::Unchanged | Change::Unfinished => {}
+            Change::Touched {
+                size,
+                mtime_secs,
+                mtime_nanos,
+            } => {
+                if let Some(entry) = self.data.files.get_mut(&relative) {
+                    entry.size = size;
+                    entry.mtime_secs = mtime_secs;
+                    entry.mtime_nanos = mtime_nanos;
+                    self.dirty = true;
+                }
+            }
+            Change::Parsed(entry) => {
+                self.data.files.insert(relative, entry);
+                self.dirty = true;
+            }
+            Change::Removed => {
+                self.dirty |= self.data.files.remove(&relative).is_some();
+            }
+        }
+    }
+
+    /// Escreve o índice num ficheiro temporário e substitui o anterior, para que uma
+    /// escrita interrompida nunca deixe um índice corrompido
+    fn save(&mut self) {
+        let Some(cache_path) = &self.cache_path else {
+            return;
+        };
+        let result = (|| -> anyhow::Result<()> {
+            if let Some(dir) = cache_path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let temporary = cache_path.with_extension("json.tmp");
+            fs::write(&temporary, serde_json::to_vec(&self.data)?)?;
+            fs::rename(&temporary, cache_path)?;
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(error) => eprintln!(
+                "⚠️  Cannot write symbol index {}: {}",
+                cache_path.display(),
+                error
+            ),
+        }
+    }
+}
+
+/// Diretoria de cache do utilizador (`$XDG_CACHE_HOME/workspace-context`)
+fn user_cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("workspace-context"))
+}
+
+/// Data de modificação em segundos e nanossegundos desde a época Unix
+fn modified(metadata: &fs::Metadata) -> (u64, u32) {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| (duration.as_secs(), duration.subsec_nanos()))
+        .unwrap_or_default()
+}
+
+/// Hash FNV-1a de 64 bits, estável entre versões do Rust (ao contrário do `DefaultHasher`)
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
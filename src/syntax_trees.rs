@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tree_sitter::{InputEdit, Point, Tree};
+
+/// Número máximo de árvores guardadas; as usadas há mais tempo são descartadas
+const MAX_TREES: usize = 64;
+
+/// Última árvore de um ficheiro e o conteúdo de onde foi gerada
+struct CachedTree {
+    content: String,
+    tree: Tree,
+    last_use: u64,
+}
+
+/// Árvores de sintaxe dos ficheiros editados recentemente, para que uma nova alteração
+/// seja analisada de forma incremental em vez de desde o início
+#[derive(Default)]
+pub(crate) struct SyntaxTrees {
+    trees: HashMap<PathBuf, CachedTree>,
+    uses: u64,
+}
+
+impl SyntaxTrees {
+    /// Retira a árvore do ficheiro, já ajustada com `Tree::edit` à diferença entre o
+    /// conteúdo antigo e o novo
+    pub(crate) fn take_edited(&mut self, file_path: &Path, new_content: &str) -> Option<Tree> {
+        let cached = self.trees.remove(file_path)?;
+        let mut tree = cached.tree;
+        tree.edit(&edit_between(&cached.content, new_content));
+        Some(tree)
+    }
+
+    pub(crate) fn insert(&mut self, file_path: &Path, content: &str, tree: Tree) {
+        if self.trees.len() >= MAX_TREES
+            && !self.trees.contains_key(file_path)
+            && let Some(oldest) = self
+                .trees
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_use)
+                .map(|(path, _)| path.clone())
+        {
+            self.trees.remove(&oldest);
+        }
+
+        self.uses += 1;
+        self.trees.insert(
+            file_path.to_path_buf(),
+            CachedTree {
+                content: content.to_string(),
+                tree,
+                last_use: self.uses,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, file_path: &Path) {
+        self.trees.remove(file_path);
+    }
+}
+
+/// Edição única que transforma `old` em `new`: o trecho entre o prefixo e o sufixo comuns
+fn edit_between(old: &str, new: &str) -> InputEdit {
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+
+    let prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_end_byte = old_bytes.len() - suffix;
+    let new_end_byte = new_bytes.len() - suffix;
+
+    InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at(old_bytes, prefix),
+        old_end_position: point_at(old_bytes, old_end_byte),
+        new_end_position: point_at(new_bytes, new_end_byte),
+    }
+}
+
+/// Linha e coluna (em bytes, base 0) de um offset
+fn point_at(bytes: &[u8], offset: usize) -> Point {
+    let before = &bytes[..offset];
+    let row = before.iter().filter(|byte| **byte == b'\n').count();
+    let column = before
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(offset, |newline| offset - newline - 1);
+    Point { row, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(row: usize, column: usize) -> Point {
+        Point { row, column }
+    }
+
+    #[test]
+    fn repeated_bytes_are_not_counted_twice() {
+        // O prefixo e o sufixo comuns não se sobrepõem: a edição é a inserção de um byte
+        let edit = edit_between("aa", "aaa");
+        assert_eq!(edit.start_byte, 2);
+        assert_eq!(edit.old_end_byte, 2);
+        assert_eq!(edit.new_end_byte, 3);
+
+        let edit = edit_between("aaa", "aa");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (2, 3, 2)
+        );
+    }
+
+    #[test]
+    fn inserts_at_start_and_end() {
+        let edit = edit_between("fn a() {}", "pub fn a() {}");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (0, 0, 4)
+        );
+
+        let edit = edit_between("a\n", "a\nb\n");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (2, 2, 4)
+        );
+        assert_eq!(edit.start_position, point(1, 0));
+        assert_eq!(edit.new_end_position, point(2, 0));
+    }
+
+    #[test]
+    fn positions_are_in_bytes() {
+        // Colunas do tree-sitter contam bytes, não caracteres
+        let edit = edit_between("é😀\nx", "é😀\nyx");
+        assert_eq!(edit.start_byte, 7);
+        assert_eq!(edit.start_position, point(1, 0));
+
+        let edit = edit_between("é😀x", "é😀yx");
+        assert_eq!(edit.start_position, point(0, 6));
+        assert_eq!(edit.new_end_position, point(0, 7));
+    }
+
+    #[test]
+    fn crlf_columns_include_the_carriage_return() {
+        let bytes = b"ab\r\ncd";
+        assert_eq!(point_at(bytes, 3), point(0, 3));
+        assert_eq!(point_at(bytes, 4), point(1, 0));
+        assert_eq!(point_at(bytes, bytes.len()), point(1, 2));
+
+        let edit = edit_between("ab\r\ncd", "ab\r\ncXd");
+        assert_eq!(edit.start_position, point(1, 1));
+        assert_eq!(edit.old_end_position, point(1, 1));
+        assert_eq!(edit.new_end_position, point(1, 2));
+    }
+
+    #[test]
+    fn identical_text_is_an_empty_edit() {
+        let edit = edit_between("same", "same");
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (4, 4, 4)
+        );
+        assert_eq!(point_at(b"", 0), point(0, 0));
+    }
+}
@@ -0,0 +1,474 @@
+use anyhow::{Result, anyhow};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tree_sitter::Node;
+use walkdir::WalkDir;
+
+use crate::imports::{SourceSet, normalize, resolve_imports};
+use crate::{IGNORED_DIRS, is_rust_test, language_for_path, parse_source};
+
+/// Extensões onde se procuram testes
+const TEST_LANGUAGE_EXTENSIONS: [&str; 9] =
+    ["rs", "js", "jsx", "mjs", "cjs", "ts", "tsx", "py", "pyi"];
+
+/// Diretorias cujo conteúdo é de testes
+const TEST_DIRS: [&str; 5] = ["tests", "test", "__tests__", "spec", "specs"];
+
+/// Funções de teste JS/TS que agrupam (`describe`) ou definem (`it`) testes
+const JS_SUITE_FUNCTIONS: [&str; 3] = ["describe", "context", "suite"];
+const JS_TEST_FUNCTIONS: [&str; 3] = ["it", "test", "specify"];
+
+/// Número máximo de testes mostrados por ficheiro
+const MAX_TESTS_PER_FILE: usize = 25;
+
+/// Ficheiros maiores do que isto não são analisados
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Teste (ou grupo de testes) encontrado num ficheiro
+struct TestCase {
+    name: String,
+    line: usize,
+    depth: usize,
+    is_suite: bool,
+}
+
+/// Ficheiro com testes e o ficheiro fonte que provavelmente cobre
+struct TestFile {
+    path: PathBuf,
+    tests: Vec<TestCase>,
+    covers: Option<(PathBuf, &'static str)>,
+}
+
+/// Encontra os testes do workspace e associa cada ficheiro de testes ao módulo que cobre.
+/// Com `source_path`, mostra apenas os testes que cobrem esse ficheiro.
+pub(crate) fn format_tests(workspace_dir: &Path, source_path: Option<&str>) -> Result<String> {
+    let files = collect_code_files(workspace_dir);
+    let sources: Vec<&PathBuf> = files.iter().filter(|path| !is_test_path(path)).collect();
+    let source_set = SourceSet::new(sources.iter().copied());
+
+    let source_filter = match source_path {
+        Some(source_path) => {
+            let path = Path::new(source_path);
+            let relative = path.strip_prefix(workspace_dir).unwrap_or(path);
+            if !workspace_dir.join(relative).is_file() {
+                return Err(anyhow!("Source file not found: {}", source_path));
+            }
+            Some(normalize(relative))
+        }
+        None => None,
+    };
+
+    let mut test_files = Vec::new();
+    for path in &files {
+        let Ok(content) = fs::read_to_string(workspace_dir.join(path)) else {
+            continue;
+        };
+        let tests = find_tests(path, &content);
+        if tests.iter().all(|test| test.is_suite) {
+            continue;
+        }
+        let covers = map_to_source(workspace_dir, path, &content, &sources, &source_set);
+        test_files.push(TestFile {
+            path: path.clone(),
+            tests,
+            covers,
+        });
+    }
+
+    if let Some(source) = &source_filter {
+        test_files.retain(|file| {
+            file.covers
+                .as_ref()
+                .is_some_and(|(covered, _)| covered == source)
+        });
+    }
+
+    let mut result = String::new();
+    match &source_filter {
+        Some(source) => result.push_str(&format!("🧪 Tests covering {}\n", source.display())),
+        None => result.push_str("🧪 Tests\n"),
+    }
+    result.push_str("══════════════════════════════════\n\n");
+
+    if test_files.is_empty() {
+        result.push_str("No tests found.\n\n");
+    }
+
+    let mut total_tests = 0;
+    let mut mapped_files = 0;
+    for file in &test_files {
+        match &file.covers {
+            Some((source, reason)) => {
+                mapped_files += 1;
+                result.push_str(&format!(
+                    "{} → {} ({})\n",
+                    file.path.display(),
+                    source.display(),
+                    reason
+                ));
+            }
+            None => result.push_str(&format!("{} (no matching source)\n", file.path.display())),
+        }
+
+        let test_count = file.tests.iter().filter(|test| !test.is_suite).count();
+        total_tests += test_count;
+
+        for test in file.tests.iter().take(MAX_TESTS_PER_FILE) {
+            let indent = "   ".repeat(test.depth);
+            let icon = if test.is_suite { "📂" } else { "🧪" };
+            result.push_str(&format!(
+                "  {}{} {} (L{})\n",
+                indent, icon, test.name, test.line
+            ));
+        }
+        if file.tests.len() > MAX_TESTS_PER_FILE {
+            result.push_str(&format!(
+                "  ... ({} more)\n",
+                file.tests.len() - MAX_TESTS_PER_FILE
+            ));
+        }
+        result.push('\n');
+    }
+
+    result.push_str(&format!(
+        "📊 Summary:\n\
+         • {} test files, {} tests\n\
+         • {} files mapped to a source module\n\
+         • Root: {}\n",
+        test_files.len(),
+        total_tests,
+        mapped_files,
+        workspace_dir.display()
+    ));
+
+    Ok(result)
+}
+
+/// Ficheiros de código (relativos ao workspace) nas linguagens suportadas
+fn collect_code_files(workspace_dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(workspace_dir)
+        .max_depth(12)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !e.file_type().is_dir()
+                || !IGNORED_DIRS
+                    .iter()
+                    .any(|&ignored| e.file_name().to_string_lossy() == ignored)
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|meta| meta.len() <= MAX_FILE_BYTES)
+        })
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| TEST_LANGUAGE_EXTENSIONS.contains(&ext))
+        })
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(workspace_dir)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// Ficheiro de testes pelo nome ou pela diretoria (`tests/`, `user.test.ts`, `test_user.py`)
+fn is_test_path(path: &Path) -> bool {
+    let in_test_dir = path.parent().is_some_and(|parent| {
+        parent
+            .components()
+            .any(|component| TEST_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref()))
+    });
+    let stem = file_stem(path);
+    in_test_dir || strip_test_affixes(&stem) != stem || stem == "tests" || stem == "conftest"
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Nome do módulo testado: `user.test` / `user_test` / `test_user` -> `user`
+fn strip_test_affixes(stem: &str) -> String {
+    for suffix in [
+        ".test", ".spec", "_test", "_tests", "_spec", "-test", "-spec", ".tests",
+    ] {
+        if let Some(base) = stem.strip_suffix(suffix)
+            && !base.is_empty()
+        {
+            return base.to_string();
+        }
+    }
+    if let Some(base) = stem.strip_prefix("test_")
+        && !base.is_empty()
+    {
+        return base.to_string();
+    }
+    stem.to_string()
+}
+
+/// Testes declarados no ficheiro, conforme a linguagem
+fn find_tests(path: &Path, content: &str) -> Vec<TestCase> {
+    let Some((language, language_name, _)) = language_for_path(path) else {
+        return Vec::new();
+    };
+    let Ok(Some(tree)) = parse_source(language, content, None, None) else {
+        return Vec::new();
+    };
+
+    let mut tests = Vec::new();
+    let root = tree.root_node();
+    match language_name {
+        "rust" => find_rust_tests(root, content, &mut tests),
+        "javascript" | "typescript" if is_test_path(path) => {
+            find_javascript_tests(root, content, 0, &mut tests)
+        }
+        "python" => find_python_tests(root, content, is_test_path(path), &mut tests),
+        _ => {}
+    }
+    tests
+}
+
+fn node_text<'a>(node: Node, content: &'a str) -> &'a str {
+    node.utf8_text(content.as_bytes()).unwrap_or_default()
+}
+
+/// Funções Rust com `#[test]`, `#[tokio::test]`, ... em qualquer módulo
+fn find_rust_tests(node: Node, content: &str, tests: &mut Vec<TestCase>) {
+    if node.kind() == "function_item"
+        && is_rust_test(node, content)
+        && let Some(name) = node.child_by_field_name("name")
+    {
+        tests.push(TestCase {
+            name: node_text(name, content).to_string(),
+            line: node.start_position().row + 1,
+            depth: 0,
+            is_suite: false,
+        });
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        find_rust_tests(child, content, tests);
+    }
+}
+
+/// Chamadas `describe(...)` / `it(...)` / `test(...)`, incluindo `it.only`, `test.each(...)(...)`
+fn find_javascript_tests(node: Node, content: &str, depth: usize, tests: &mut Vec<TestCase>) {
+    let mut child_depth = depth;
+
+    if node.kind() == "call_expression"
+        && let Some(function) = node.child_by_field_name("function")
+        && let Some(name) = node
+            .child_by_field_name("arguments")
+            .and_then(|arguments| arguments.named_child(0))
+            .filter(|argument| matches!(argument.kind(), "string" | "template_string"))
+    {
+        let callee = javascript_test_callee(function, content);
+        let is_suite = JS_SUITE_FUNCTIONS.contains(&callee);
+        if is_suite || JS_TEST_FUNCTIONS.contains(&callee) {
+            tests.push(TestCase {
+                name: node_text(name, content)
+                    .trim_matches(|c| c == '\'' || c == '"' || c == '`')
+                    .to_string(),
+                line: node.start_position().row + 1,
+                depth,
+                is_suite,
+            });
+            if is_suite {
+                child_depth += 1;
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        find_javascript_tests(child, content, child_depth, tests);
+    }
+}
+
+/// Nome base da função chamada: `describe.only` -> `describe`, `it.each(x)` -> `it`
+fn javascript_test_callee<'a>(function: Node, content: &'a str) -> &'a str {
+    let mut node = function;
+    loop {
+        match node.kind() {
+            "identifier" => return node_text(node, content),
+            "member_expression" => match node.child_by_field_name("object") {
+                Some(object) => node = object,
+                None => return "",
+            },
+            "call_expression" => match node.child_by_field_name("function") {
+                Some(inner) => node = inner,
+                None => return "",
+            },
+            _ => return "",
+        }
+    }
+}
+
+/// Classes `unittest.TestCase` / `Test*` e funções `test_*`. As funções soltas só contam
+/// em ficheiros de testes, que é onde o pytest as recolhe.
+fn find_python_tests(node: Node, content: &str, is_test_file: bool, tests: &mut Vec<TestCase>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let definition = if child.kind() == "decorated_definition" {
+            match child.child_by_field_name("definition") {
+                Some(definition) => definition,
+                None => continue,
+            }
+        } else {
+            child
+        };
+        let Some(name) = definition
+            .child_by_field_name("name")
+            .map(|name| node_text(name, content))
+        else {
+            continue;
+        };
+
+        match definition.kind() {
+            "function_definition" if is_test_file && name.starts_with("test") => {
+                tests.push(TestCase {
+                    name: name.to_string(),
+                    line: definition.start_position().row + 1,
+                    depth: 0,
+                    is_suite: false,
+                });
+            }
+            "class_definition" => {
+                let is_test_case = definition
+                    .child_by_field_name("superclasses")
+                    .is_some_and(|bases| node_text(bases, content).contains("TestCase"));
+                if !(is_test_case || is_test_file && name.starts_with("Test")) {
+                    continue;
+                }
+                tests.push(TestCase {
+                    name: name.to_string(),
+                    line: definition.start_position().row + 1,
+                    depth: 0,
+                    is_suite: true,
+                });
+                let Some(body) = definition.child_by_field_name("body") else {
+                    continue;
+                };
+                let mut body_cursor = body.walk();
+                for member in body.named_children(&mut body_cursor) {
+                    let method = if member.kind() == "decorated_definition" {
+                        member.child_by_field_name("definition")
+                    } else {
+                        Some(member)
+                    };
+                    if let Some(method) =
+                        method.filter(|method| method.kind() == "function_definition")
+                        && let Some(method_name) = method.child_by_field_name("name")
+                        && node_text(method_name, content).starts_with("test")
+                    {
+                        tests.push(TestCase {
+                            name: node_text(method_name, content).to_string(),
+                            line: method.start_position().row + 1,
+                            depth: 1,
+                            is_suite: false,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Ficheiro fonte mais provável coberto pelos testes: o próprio ficheiro (testes unitários
+/// Rust), um import cujo nome coincide, o ficheiro com o mesmo nome ou o primeiro import
+fn map_to_source(
+    workspace_dir: &Path,
+    test_path: &Path,
+    content: &str,
+    sources: &[&PathBuf],
+    source_set: &SourceSet,
+) -> Option<(PathBuf, &'static str)> {
+    let is_rust = test_path.extension().is_some_and(|ext| ext == "rs");
+    let stem = file_stem(test_path);
+
+    // Testes unitários Rust vivem no próprio módulo
+    if is_rust && !is_test_path(test_path) {
+        return Some((test_path.to_path_buf(), "unit tests"));
+    }
+    // `src/user/tests.rs` testa o módulo `user`
+    if is_rust
+        && stem == "tests"
+        && let Some(parent) = test_path.parent()
+        && let Some(module) = rust_module_file(parent, sources)
+    {
+        return Some((module, "unit tests"));
+    }
+
+    let base = strip_test_affixes(&stem);
+    let imports = resolve_imports(workspace_dir, test_path, content, source_set);
+
+    if let Some(import) = imports.iter().find(|import| module_name(import) == base) {
+        return Some((import.clone(), "import and name"));
+    }
+    if let Some(named) = closest_by_name(test_path, &base, sources) {
+        return Some((named, "name"));
+    }
+    imports.into_iter().next().map(|import| (import, "import"))
+}
+
+/// Nome do módulo de um ficheiro fonte (`user/mod.rs` e `user/__init__.py` -> `user`)
+fn module_name(path: &Path) -> String {
+    let stem = file_stem(path);
+    if matches!(stem.as_str(), "mod" | "__init__" | "index") {
+        path.parent()
+            .and_then(|parent| parent.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or(stem)
+    } else {
+        stem
+    }
+}
+
+/// Ficheiro fonte com o nome do módulo testado, preferindo o que partilha mais
+/// diretorias com o ficheiro de testes (`tests/api/user.rs` -> `src/api/user.rs`)
+fn closest_by_name(test_path: &Path, base: &str, sources: &[&PathBuf]) -> Option<PathBuf> {
+    let test_dirs: BTreeSet<String> = directory_names(test_path);
+
+    sources
+        .iter()
+        .filter(|source| module_name(source) == base)
+        .max_by_key(|source| {
+            let shared = directory_names(source).intersection(&test_dirs).count();
+            (shared, std::cmp::Reverse(source.components().count()))
+        })
+        .map(|source| (*source).clone())
+}
+
+fn directory_names(path: &Path) -> BTreeSet<String> {
+    path.parent()
+        .map(|parent| {
+            parent
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `dir.rs` ou `dir/mod.rs` para a diretoria de um módulo Rust
+fn rust_module_file(dir: &Path, sources: &[&PathBuf]) -> Option<PathBuf> {
+    [dir.with_extension("rs"), dir.join("mod.rs")]
+        .into_iter()
+        .find(|candidate| sources.contains(&candidate))
+}
@@ -0,0 +1,11 @@
+/// Caracteres ASCII por token, em média, no código e no texto em inglês
+const ASCII_CHARS_PER_TOKEN: usize = 4;
+
+/// Estimativa do número de tokens de um texto. Texto ASCII conta um token por cada
+/// quatro caracteres; outros caracteres (acentos, emojis, desenho da árvore) contam
+/// um token cada, porque os tokenizadores raramente os juntam.
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    let ascii = text.bytes().filter(u8::is_ascii).count();
+    let other = text.chars().filter(|c| !c.is_ascii()).count();
+    ascii.div_ceil(ASCII_CHARS_PER_TOKEN) + other
+}
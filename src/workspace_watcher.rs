@@ -0,0 +1,278 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+use crate::language_config::{LanguageConfig, PROJECT_CONFIG_DIR};
+use crate::semantic_index::SemanticIndex;
+use crate::symbol_index::SymbolIndex;
+use crate::{
+    DEFAULT_MAX_FILE_BYTES, FileSelection, collect_project_files_with_limits, is_ignored_dir_name,
+    project_file_priority,
+};
+
+/// Silêncio necessário depois de uma alteração antes de atualizar o índice
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Espera máxima durante uma rajada contínua de alterações
+const MAX_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Estado de um workspace: configuração, índice de símbolos e listas de ficheiros,
+/// mantidos atualizados por um watcher enquanto o servidor corre
+pub(crate) struct Workspace {
+    root: PathBuf,
+    pub(crate) config: LanguageConfig,
+    pub(crate) index: SymbolIndex,
+    /// Ficheiros selecionados por `(max_files, max_depth)`, invalidados quando
+    /// ficheiros são criados ou apagados
+    file_lists: HashMap<(usize, usize), FileSelection>,
+    /// Vetores para `semantic_search`, criados no primeiro pedido
+    semantic: Option<SemanticIndex>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl Workspace {
+    fn new(root: &Path) -> Self {
+        let config = LanguageConfig::load(root);
+        let index = SymbolIndex::open(root, &config);
+        Workspace {
+            root: root.to_path_buf(),
+            config,
+            index,
+            file_lists: HashMap::new(),
+            semantic: None,
+            watcher: None,
+        }
+    }
+
+    /// Ficheiros do projeto com os limites indicados; sem watcher o workspace é
+    /// percorrido em cada chamada, e seleções interrompidas pelo `deadline` nunca
+    /// são guardadas
+    pub(crate) fn files(
+        &mut self,
+        max_files: usize,
+        max_depth: usize,
+        deadline: Option<Instant>,
+    ) -> FileSelection {
+        if self.watcher.is_some()
+            && let Some(selection) = self.file_lists.get(&(max_files, max_depth))
+        {
+            return selection.clone();
+        }
+
+        let selection = collect_project_files_with_limits(
+            &self.root,
+            max_files,
+            max_depth,
+            &self.config,
+            deadline,
+        );
+        if self.watcher.is_some() && !selection.timed_out {
+            self.file_lists
+                .insert((max_files, max_depth), selection.clone());
+        }
+        selection
+    }
+
+    /// Garante que os ficheiros estão no índice. Com watcher cada ficheiro só é
+    /// verificado uma vez; sem watcher todos são verificados em cada chamada
+    pub(crate) fn index_files(&mut self, files: &[PathBuf], deadline: Option<Instant>) {
+        if self.watcher.is_some() {
+            self.index.index_unchecked(files, &self.config, deadline);
+        } else {
+            self.index.refresh(files, &self.config, deadline);
+        }
+    }
+
+    /// Índice semântico, aberto no primeiro pedido e atualizado com os ficheiros
+    /// que mudaram desde então
+    pub(crate) fn semantic_index(&mut self, files: &[PathBuf]) -> &SemanticIndex {
+        let semantic = self
+            .semantic
+            .get_or_insert_with(|| SemanticIndex::open(&self.index, &self.config));
+        semantic.update(&self.root, files, &self.index);
+        semantic
+    }
+
+    /// Observa a diretoria e as subdiretorias que não são ignoradas. Se alguma não
+    /// puder ser observada (ex.: limite `max_user_watches` do inotify), o watcher é
+    /// descartado e o workspace passa a ser verificado no disco em cada pedido.
+    fn watch_tree(&mut self, dir: &Path) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+
+        let directories = WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| {
+                e.file_type().is_dir()
+                    && (e.depth() == 0 || !is_ignored_dir_name(&e.file_name().to_string_lossy()))
+            })
+            .flatten();
+
+        for directory in directories {
+            if let Err(error) = watcher.watch(directory.path(), RecursiveMode::NonRecursive) {
+                eprintln!(
+                    "⚠️  Cannot watch {}: {} (checking files on every request)",
+                    directory.path().display(),
+                    error
+                );
+                self.watcher = None;
+                self.file_lists.clear();
+                return;
+            }
+        }
+    }
+
+    /// Aplica um lote de alterações: reanalisa os ficheiros modificados, esquece os
+    /// apagados e passa a observar as diretorias novas
+    fn apply_changes(&mut self, paths: BTreeSet<PathBuf>) {
+        let mut structure_changed = false;
+        let mut config_changed = false;
+
+        for path in paths {
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            if relative
+                .components()
+                .any(|component| is_ignored_dir_name(&component.as_os_str().to_string_lossy()))
+            {
+                continue;
+            }
+            if relative.starts_with(PROJECT_CONFIG_DIR) {
+                config_changed = true;
+                continue;
+            }
+
+            if path.is_dir() {
+                self.watch_tree(&path);
+                structure_changed = true;
+            } else if path.is_file() {
+                if project_file_priority(&path, &self.config).is_none() {
+                    continue;
+                }
+                structure_changed |= self.index.symbols(&path).is_none();
+                // Ficheiros grandes não são analisados com o workspace bloqueado: ficam
+                // por verificar até um pedido os analisar dentro do seu limite de tempo
+                if fs::metadata(&path).is_ok_and(|metadata| metadata.len() > DEFAULT_MAX_FILE_BYTES)
+                {
+                    self.index.mark_unchecked(&path);
+                    continue;
+                }
+                self.index.update(&path, &self.config);
+            } else {
+                self.index.remove(&path);
+                structure_changed = true;
+            }
+        }
+
+        // Queries ou gramáticas alteradas invalidam todos os símbolos
+        if config_changed {
+            self.config = LanguageConfig::load(&self.root);
+            self.index = SymbolIndex::open(&self.root, &self.config);
+            self.semantic = None;
+            structure_changed = true;
+        }
+        if structure_changed {
+            self.file_lists.clear();
+        }
+        self.index.save_if_dirty();
+    }
+}
+
+/// Workspaces abertos pelo servidor, partilhados entre os handlers
+#[derive(Default)]
+pub(crate) struct Workspaces {
+    watched: Mutex<HashMap<PathBuf, Arc<Mutex<Workspace>>>>,
+}
+
+impl Workspaces {
+    /// Estado do workspace, começando a observá-lo no primeiro pedido. Se o watcher
+    /// não puder ser criado, é devolvido um estado novo em cada pedido; se não puder
+    /// observar toda a árvore, o estado é mantido mas sem watcher.
+    pub(crate) fn get(&self, root: &Path) -> Arc<Mutex<Workspace>> {
+        let mut watched = lock(&self.watched);
+        if let Some(workspace) = watched.get(root) {
+            return workspace.clone();
+        }
+
+        let workspace = Arc::new(Mutex::new(Workspace::new(root)));
+        let (sender, receiver) = mpsc::channel();
+        match notify::recommended_watcher(sender) {
+            Ok(watcher) => {
+                {
+                    let mut state = lock(&workspace);
+                    state.watcher = Some(watcher);
+                    state.watch_tree(root);
+                }
+                let weak = Arc::downgrade(&workspace);
+                thread::spawn(move || debounce_events(weak, receiver));
+                watched.insert(root.to_path_buf(), workspace.clone());
+            }
+            Err(error) => eprintln!("⚠️  Cannot watch {}: {}", root.display(), error),
+        }
+
+        workspace
+    }
+
+    /// Workspace já aberto que contém o caminho (o mais interior, se houver vários)
+    pub(crate) fn find_for(&self, path: &Path) -> Option<Arc<Mutex<Workspace>>> {
+        lock(&self.watched)
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, workspace)| workspace.clone())
+    }
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Agrupa os eventos até haver `DEBOUNCE` sem alterações e aplica-os de uma vez.
+/// Termina quando o workspace (e com ele o watcher) é libertado.
+fn debounce_events(workspace: Weak<Mutex<Workspace>>, receiver: Receiver<notify::Result<Event>>) {
+    while let Ok(first) = receiver.recv() {
+        let mut paths = BTreeSet::new();
+        collect_event_paths(first, &mut paths);
+
+        let started = Instant::now();
+        while started.elapsed() < MAX_DEBOUNCE {
+            match receiver.recv_timeout(DEBOUNCE) {
+                Ok(event) => collect_event_paths(event, &mut paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if paths.is_empty() {
+            continue;
+        }
+        let Some(workspace) = workspace.upgrade() else {
+            return;
+        };
+        lock(&workspace).apply_changes(paths);
+    }
+}
+
+fn collect_event_paths(event: notify::Result<Event>, paths: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) => {
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                paths.extend(event.paths);
+            }
+        }
+        Err(error) => eprintln!("⚠️  Watch error: {}", error),
+    }
+}